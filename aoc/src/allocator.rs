@@ -0,0 +1,211 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "alloc-profile")]
+#[global_allocator]
+pub static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationStats {
+    pub peak_bytes: usize,
+    pub allocations: usize,
+    pub allocated_bytes: usize,
+}
+
+fn format_bytes(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if 0 == unit {
+        format!("{} {}", bytes, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+impl fmt::Display for AllocationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "peak {}, {} allocations, {} allocated",
+            format_bytes(self.peak_bytes),
+            self.allocations,
+            format_bytes(self.allocated_bytes)
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    baseline: AtomicUsize,
+    allocations: AtomicUsize,
+    allocated_bytes: AtomicUsize,
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        CountingAllocator::new()
+    }
+}
+
+impl CountingAllocator {
+    pub const fn new() -> CountingAllocator {
+        CountingAllocator {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            baseline: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            allocated_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    fn record_allocation(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.allocated_bytes.fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn record_deallocation(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    // Starts a new measurement; the peak is reported relative to the heap
+    // that was already live at this point
+    pub fn reset(&self) {
+        let current = self.current.load(Ordering::Relaxed);
+        self.baseline.store(current, Ordering::Relaxed);
+        self.peak.store(current, Ordering::Relaxed);
+        self.allocations.store(0, Ordering::Relaxed);
+        self.allocated_bytes.store(0, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> AllocationStats {
+        AllocationStats {
+            peak_bytes: self
+                .peak
+                .load(Ordering::Relaxed)
+                .saturating_sub(self.baseline.load(Ordering::Relaxed)),
+            allocations: self.allocations.load(Ordering::Relaxed),
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            self.record_allocation(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            self.record_allocation(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        self.record_deallocation(layout.size());
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_pointer = System.realloc(pointer, layout, new_size);
+        if !new_pointer.is_null() {
+            self.record_deallocation(layout.size());
+            self.record_allocation(new_size);
+        }
+        new_pointer
+    }
+}
+
+pub fn reset() {
+    #[cfg(feature = "alloc-profile")]
+    ALLOCATOR.reset();
+}
+
+// Returns None unless the counting allocator is installed
+pub fn stats() -> Option<AllocationStats> {
+    #[cfg(feature = "alloc-profile")]
+    return Some(ALLOCATOR.stats());
+    #[cfg(not(feature = "alloc-profile"))]
+    None
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_allocator() {
+        let allocator = CountingAllocator::new();
+        let small = Layout::from_size_align(16, 8).unwrap();
+        let large = Layout::from_size_align(64, 8).unwrap();
+        unsafe {
+            let first = allocator.alloc(small);
+            allocator.reset();
+            let second = allocator.alloc_zeroed(large);
+            let second = allocator.realloc(second, large, 128);
+            allocator.dealloc(second, Layout::from_size_align(128, 8).unwrap());
+            assert_eq!(
+                AllocationStats {
+                    peak_bytes: 128,
+                    allocations: 2,
+                    allocated_bytes: 192,
+                },
+                allocator.stats()
+            );
+            allocator.dealloc(first, small);
+        }
+        allocator.reset();
+        assert_eq!(AllocationStats::default(), allocator.stats());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!("0 B", format_bytes(0));
+        assert_eq!("1023 B", format_bytes(1023));
+        assert_eq!("1.5 KiB", format_bytes(1536));
+        assert_eq!("2.0 MiB", format_bytes(2 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_display_allocation_stats() {
+        let stats = AllocationStats {
+            peak_bytes: 2048,
+            allocations: 3,
+            allocated_bytes: 4096,
+        };
+        assert_eq!(
+            "peak 2.0 KiB, 3 allocations, 4.0 KiB allocated",
+            stats.to_string()
+        );
+    }
+}
@@ -0,0 +1,623 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+
+const CHECKPOINT_INTERVAL: usize = 64;
+
+pub trait Simulation: Clone {
+    // Advances one step, returning false without changing anything once the
+    // simulation has finished
+    fn step(&mut self) -> bool;
+    fn fields(&self) -> Vec<(String, i64)>;
+    fn panes(&self) -> Vec<(String, Vec<String>)>;
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn compare(&self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BreakpointError {
+    MissingComparison,
+    UnknownComparison(String),
+    MissingField,
+    InvalidValue(String),
+}
+
+impl fmt::Display for BreakpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BreakpointError::MissingComparison => write!(f, "expected ==, !=, <, <=, > or >="),
+            BreakpointError::UnknownComparison(comparison) => {
+                write!(f, "unknown comparison {}", comparison)
+            }
+            BreakpointError::MissingField => write!(f, "expected a field name"),
+            BreakpointError::InvalidValue(value) => write!(f, "{} is not an integer", value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Breakpoint {
+    pub field: String,
+    pub comparison: Comparison,
+    pub value: i64,
+}
+
+impl FromStr for Breakpoint {
+    type Err = BreakpointError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let is_operator = |character: char| "=!<>".contains(character);
+        let start = input
+            .find(is_operator)
+            .ok_or(BreakpointError::MissingComparison)?;
+        let end = input[start..]
+            .find(|character: char| !is_operator(character))
+            .map_or(input.len(), |offset| start + offset);
+        let comparison = match &input[start..end] {
+            "==" | "=" => Comparison::Equal,
+            "!=" => Comparison::NotEqual,
+            "<" => Comparison::Less,
+            "<=" => Comparison::LessOrEqual,
+            ">" => Comparison::Greater,
+            ">=" => Comparison::GreaterOrEqual,
+            other => return Err(BreakpointError::UnknownComparison(other.to_string())),
+        };
+        let field = input[..start].trim().to_lowercase();
+        if field.is_empty() {
+            return Err(BreakpointError::MissingField);
+        }
+        let value = input[end..].trim();
+        let value = value
+            .parse::<i64>()
+            .map_err(|_| BreakpointError::InvalidValue(value.to_string()))?;
+        Ok(Breakpoint {
+            field,
+            comparison,
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.comparison, self.value)
+    }
+}
+
+impl Breakpoint {
+    pub fn matches(&self, fields: &[(String, i64)]) -> bool {
+        fields.iter().any(|(name, value)| {
+            name.to_lowercase() == self.field && self.comparison.compare(*value, self.value)
+        })
+    }
+}
+
+// Keeps a snapshot every CHECKPOINT_INTERVAL steps and replays from the
+// nearest one when moving backwards, so long runs stay cheap to rewind
+#[derive(Debug)]
+pub struct Timeline<S: Simulation> {
+    checkpoints: Vec<S>,
+    interval: usize,
+    current: S,
+    position: usize,
+    furthest: usize,
+    finished: bool,
+}
+
+impl<S: Simulation> Timeline<S> {
+    pub fn new(initial: S) -> Timeline<S> {
+        Timeline::with_interval(initial, CHECKPOINT_INTERVAL)
+    }
+
+    pub fn with_interval(initial: S, interval: usize) -> Timeline<S> {
+        Timeline {
+            checkpoints: vec![initial.clone()],
+            interval: interval.max(1),
+            current: initial,
+            position: 0,
+            furthest: 0,
+            finished: false,
+        }
+    }
+
+    pub fn current(&self) -> &S {
+        &self.current
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn furthest(&self) -> usize {
+        self.furthest
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn forward(&mut self) -> bool {
+        if self.finished && self.position == self.furthest {
+            return false;
+        }
+        if !self.current.step() {
+            self.finished = true;
+            return false;
+        }
+        self.position += 1;
+        self.furthest = self.furthest.max(self.position);
+        if self.position.is_multiple_of(self.interval)
+            && self.checkpoints.len() == self.position / self.interval
+        {
+            self.checkpoints.push(self.current.clone());
+        }
+        true
+    }
+
+    pub fn back(&mut self) -> bool {
+        if 0 == self.position {
+            return false;
+        }
+        self.seek(self.position - 1);
+        true
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        if position < self.position {
+            let checkpoint = (position / self.interval).min(self.checkpoints.len() - 1);
+            self.current = self.checkpoints[checkpoint].clone();
+            self.position = checkpoint * self.interval;
+        }
+        while self.position < position && self.forward() {}
+    }
+
+    // Steps forward until a breakpoint matches, returning its index
+    pub fn run_until(&mut self, breakpoints: &[Breakpoint]) -> Option<usize> {
+        while self.forward() {
+            let fields = self.current.fields();
+            if let Some(index) = breakpoints
+                .iter()
+                .position(|breakpoint| breakpoint.matches(&fields))
+            {
+                return Some(index);
+            }
+        }
+        None
+    }
+}
+
+// Returns the part to explore when --explore is present, defaulting to 1
+pub fn requested_part(args: impl IntoIterator<Item = String>) -> Option<u8> {
+    let args = args.into_iter().collect::<Vec<String>>();
+    if !args.iter().any(|arg| "--explore" == arg) {
+        return None;
+    }
+    let part = args
+        .iter()
+        .position(|arg| "--part" == arg)
+        .and_then(|index| args.get(index + 1))
+        .and_then(|part| part.parse::<u8>().ok());
+    Some(part.unwrap_or(1))
+}
+
+#[cfg(feature = "explorer")]
+mod tui {
+    use super::{Breakpoint, Simulation, Timeline};
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::widgets::{Block, Borders, Gauge, Paragraph};
+    use ratatui::{DefaultTerminal, Frame};
+    use std::io;
+
+    const JUMP: usize = 10;
+
+    pub struct App<S: Simulation> {
+        pub timeline: Timeline<S>,
+        pub breakpoints: Vec<Breakpoint>,
+        pub input: Option<String>,
+        pub message: String,
+    }
+
+    impl<S: Simulation> App<S> {
+        pub fn new(initial: S) -> App<S> {
+            App {
+                timeline: Timeline::new(initial),
+                breakpoints: vec![],
+                input: None,
+                message: String::new(),
+            }
+        }
+
+        // Returns false when the explorer should quit
+        pub fn handle_key(&mut self, code: KeyCode) -> bool {
+            if let Some(input) = self.input.as_mut() {
+                match code {
+                    KeyCode::Char(character) => input.push(character),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Enter => {
+                        match input.parse::<Breakpoint>() {
+                            Ok(breakpoint) => {
+                                self.message = format!("Added breakpoint {}", breakpoint);
+                                self.breakpoints.push(breakpoint);
+                            }
+                            Err(error) => self.message = format!("Invalid breakpoint: {}", error),
+                        }
+                        self.input = None;
+                    }
+                    KeyCode::Esc => self.input = None,
+                    _ => {}
+                }
+                return true;
+            }
+            self.message.clear();
+            match code {
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                KeyCode::Right | KeyCode::Char('l') => {
+                    self.timeline.forward();
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.timeline.back();
+                }
+                KeyCode::PageDown => self.timeline.seek(self.timeline.position() + JUMP),
+                KeyCode::PageUp => self
+                    .timeline
+                    .seek(self.timeline.position().saturating_sub(JUMP)),
+                KeyCode::Home => self.timeline.seek(0),
+                KeyCode::End => self.timeline.seek(usize::MAX),
+                KeyCode::Char('c') => {
+                    self.message = match self.timeline.run_until(&self.breakpoints) {
+                        Some(index) => format!("Hit breakpoint {}", self.breakpoints[index]),
+                        None => "Reached the end".to_string(),
+                    }
+                }
+                KeyCode::Char('b') => self.input = Some(String::new()),
+                KeyCode::Char('d') => {
+                    if let Some(breakpoint) = self.breakpoints.pop() {
+                        self.message = format!("Removed breakpoint {}", breakpoint);
+                    }
+                }
+                _ => {}
+            }
+            true
+        }
+
+        pub fn draw(&self, frame: &mut Frame) {
+            let areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(self.breakpoints.len() as u16 + 2),
+                    Constraint::Length(1),
+                ])
+                .split(frame.area());
+            let furthest = self.timeline.furthest().max(1);
+            let label = format!(
+                "step {} / {}{}",
+                self.timeline.position(),
+                self.timeline.furthest(),
+                if self.timeline.is_finished() { "" } else { "+" }
+            );
+            frame.render_widget(
+                Gauge::default()
+                    .block(Block::default().borders(Borders::ALL).title("Timeline"))
+                    .ratio(self.timeline.position() as f64 / furthest as f64)
+                    .label(label),
+                areas[0],
+            );
+            let current = self.timeline.current();
+            let mut panes = vec![(
+                "Fields".to_string(),
+                current
+                    .fields()
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value))
+                    .collect::<Vec<String>>(),
+            )];
+            panes.extend(current.panes());
+            let pane_areas = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    panes
+                        .iter()
+                        .map(|_| Constraint::Ratio(1, panes.len() as u32))
+                        .collect::<Vec<Constraint>>(),
+                )
+                .split(areas[1]);
+            for ((title, lines), area) in panes.iter().zip(pane_areas.iter()) {
+                frame.render_widget(
+                    Paragraph::new(lines.join("\n"))
+                        .block(Block::default().borders(Borders::ALL).title(title.as_str())),
+                    *area,
+                );
+            }
+            frame.render_widget(
+                Paragraph::new(
+                    self.breakpoints
+                        .iter()
+                        .map(|breakpoint| breakpoint.to_string())
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                )
+                .block(Block::default().borders(Borders::ALL).title("Breakpoints")),
+                areas[2],
+            );
+            let status = match &self.input {
+                Some(input) => format!("breakpoint> {}", input),
+                None if !self.message.is_empty() => self.message.clone(),
+                None => "←/→ step  PgUp/PgDn jump  Home/End  c continue  b break  d delete  q quit"
+                    .to_string(),
+            };
+            frame.render_widget(Paragraph::new(status), areas[3]);
+        }
+
+        pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+            loop {
+                terminal.draw(|frame| self.draw(frame))?;
+                if let Event::Key(key) = event::read()? {
+                    if KeyEventKind::Press == key.kind && !self.handle_key(key.code) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "explorer")]
+pub use tui::App;
+
+#[cfg(feature = "explorer")]
+#[cfg(not(tarpaulin_include))]
+pub fn explore<S: Simulation>(initial: S) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new(initial).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter {
+        value: i64,
+        limit: i64,
+    }
+
+    impl Simulation for Counter {
+        fn step(&mut self) -> bool {
+            if self.value == self.limit {
+                return false;
+            }
+            self.value += 1;
+            true
+        }
+
+        fn fields(&self) -> Vec<(String, i64)> {
+            vec![("Value".to_string(), self.value)]
+        }
+
+        fn panes(&self) -> Vec<(String, Vec<String>)> {
+            vec![("Counter".to_string(), vec![self.value.to_string()])]
+        }
+    }
+
+    fn counter(limit: i64) -> Counter {
+        Counter { value: 0, limit }
+    }
+
+    #[test]
+    fn test_breakpoint_from_str() {
+        assert_eq!(
+            Ok(Breakpoint {
+                field: "x".to_string(),
+                comparison: Comparison::Equal,
+                value: 21,
+            }),
+            "X == 21".parse::<Breakpoint>()
+        );
+        assert_eq!(
+            Ok(Breakpoint {
+                field: "monkey3.items".to_string(),
+                comparison: Comparison::Greater,
+                value: 10,
+            }),
+            "monkey3.items>10".parse::<Breakpoint>()
+        );
+        assert_eq!(
+            Comparison::GreaterOrEqual,
+            "a >= -1".parse::<Breakpoint>().unwrap().comparison
+        );
+        assert_eq!(
+            Err(BreakpointError::MissingComparison),
+            "x 21".parse::<Breakpoint>()
+        );
+        assert_eq!(
+            Err(BreakpointError::UnknownComparison("=>".to_string())),
+            "x => 21".parse::<Breakpoint>()
+        );
+        assert_eq!(
+            Err(BreakpointError::MissingField),
+            " < 21".parse::<Breakpoint>()
+        );
+        assert_eq!(
+            Err(BreakpointError::InvalidValue("twenty".to_string())),
+            "x < twenty".parse::<Breakpoint>()
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_matches() {
+        let fields = vec![("X".to_string(), 21), ("cycle".to_string(), 3)];
+        assert!("x == 21".parse::<Breakpoint>().unwrap().matches(&fields));
+        assert!("cycle <= 3".parse::<Breakpoint>().unwrap().matches(&fields));
+        assert!(!"cycle != 3".parse::<Breakpoint>().unwrap().matches(&fields));
+        assert!(!"y == 21".parse::<Breakpoint>().unwrap().matches(&fields));
+        assert_eq!(
+            "cycle < 3",
+            "Cycle<3".parse::<Breakpoint>().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_timeline_forward_and_back() {
+        let mut timeline = Timeline::with_interval(counter(5), 2);
+        assert!(!timeline.back());
+        for expected in 1..=5 {
+            assert!(timeline.forward());
+            assert_eq!(expected, timeline.current().value);
+        }
+        assert!(!timeline.forward());
+        assert!(timeline.is_finished());
+        assert_eq!(5, timeline.furthest());
+        assert_eq!(3, timeline.checkpoints.len());
+        assert!(timeline.back());
+        assert_eq!(4, timeline.position());
+        assert_eq!(4, timeline.current().value);
+        assert!(timeline.back());
+        assert_eq!(3, timeline.current().value);
+    }
+
+    #[test]
+    fn test_timeline_seek() {
+        let mut timeline = Timeline::with_interval(counter(100), 8);
+        timeline.seek(50);
+        assert_eq!(50, timeline.current().value);
+        timeline.seek(3);
+        assert_eq!(3, timeline.current().value);
+        assert_eq!(50, timeline.furthest());
+        assert!(!timeline.is_finished());
+        timeline.seek(usize::MAX);
+        assert_eq!(100, timeline.position());
+        assert!(timeline.is_finished());
+    }
+
+    #[test]
+    fn test_timeline_run_until() {
+        let mut timeline = Timeline::new(counter(30));
+        let breakpoints = vec![
+            "value > 25".parse::<Breakpoint>().unwrap(),
+            "value == 12".parse::<Breakpoint>().unwrap(),
+        ];
+        assert_eq!(Some(1), timeline.run_until(&breakpoints));
+        assert_eq!(12, timeline.current().value);
+        assert_eq!(Some(0), timeline.run_until(&breakpoints));
+        assert_eq!(26, timeline.current().value);
+        assert_eq!(None, timeline.run_until(&[]));
+        assert_eq!(30, timeline.current().value);
+    }
+
+    #[test]
+    fn test_requested_part() {
+        let args = |input: &[&str]| input.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(None, requested_part(args(&["--variant", "all"])));
+        assert_eq!(Some(1), requested_part(args(&["--explore"])));
+        assert_eq!(Some(2), requested_part(args(&["--explore", "--part", "2"])));
+    }
+
+    #[cfg(feature = "explorer")]
+    #[test]
+    fn test_app_handle_key() {
+        use ratatui::crossterm::event::KeyCode;
+        let mut app = App::new(counter(30));
+        assert!(app.handle_key(KeyCode::Right));
+        assert!(app.handle_key(KeyCode::PageDown));
+        assert_eq!(11, app.timeline.current().value);
+        app.handle_key(KeyCode::Left);
+        assert_eq!(10, app.timeline.current().value);
+        app.handle_key(KeyCode::Char('b'));
+        for character in "value == 20".chars() {
+            app.handle_key(KeyCode::Char(character));
+        }
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(1, app.breakpoints.len());
+        app.handle_key(KeyCode::Char('c'));
+        assert_eq!(20, app.timeline.current().value);
+        assert_eq!("Hit breakpoint value == 20", app.message);
+        app.handle_key(KeyCode::Char('b'));
+        app.handle_key(KeyCode::Char('?'));
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(1, app.breakpoints.len());
+        assert!(app.message.starts_with("Invalid breakpoint"));
+        app.handle_key(KeyCode::Char('d'));
+        assert!(app.breakpoints.is_empty());
+        app.handle_key(KeyCode::End);
+        assert_eq!(30, app.timeline.current().value);
+        app.handle_key(KeyCode::Home);
+        assert_eq!(0, app.timeline.current().value);
+        assert!(!app.handle_key(KeyCode::Char('q')));
+    }
+
+    #[cfg(feature = "explorer")]
+    #[test]
+    fn test_app_draw() {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+        let mut app = App::new(counter(3));
+        app.timeline.forward();
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let content = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(content.contains("step 1 / 1+"));
+        assert!(content.contains("Value = 1"));
+        assert!(content.contains("Counter"));
+    }
+}
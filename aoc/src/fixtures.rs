@@ -0,0 +1,193 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fs::{create_dir_all, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref ARTICLE_PATTERN: Regex =
+        Regex::new(r#"(?s)<article class="day-desc">(?P<body>.*?)</article>"#).unwrap();
+    static ref EXAMPLE_PATTERN: Regex =
+        Regex::new(r"(?s)<pre><code>(?P<example>.*?)</code></pre>").unwrap();
+    static ref ANSWER_PATTERN: Regex = Regex::new(
+        r"(?s)<code><em>(?P<code_em>[^<]*)</em></code>|<em><code>(?P<em_code>[^<]*)</code></em>"
+    )
+    .unwrap();
+    static ref TAG_PATTERN: Regex = Regex::new(r"<[^>]*>").unwrap();
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Fixtures {
+    pub examples: Vec<String>,
+    pub answers: Vec<String>,
+}
+
+fn unescape(text: &str) -> String {
+    TAG_PATTERN
+        .replace_all(text, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// Each part of a saved puzzle page is its own article; the last emphasized
+// code span in a part is the example's expected answer
+pub fn extract(html: &str) -> Fixtures {
+    let mut articles = ARTICLE_PATTERN
+        .captures_iter(html)
+        .map(|captures| captures.name("body").unwrap().as_str())
+        .collect::<Vec<&str>>();
+    if articles.is_empty() {
+        articles.push(html);
+    }
+    let mut examples: Vec<String> = Vec::new();
+    let mut answers = Vec::new();
+    for article in articles {
+        for captures in EXAMPLE_PATTERN.captures_iter(article) {
+            let example = unescape(&captures["example"]);
+            if !examples.contains(&example) {
+                examples.push(example);
+            }
+        }
+        if let Some(captures) = ANSWER_PATTERN.captures_iter(article).last() {
+            let answer = captures
+                .name("code_em")
+                .or_else(|| captures.name("em_code"))
+                .unwrap()
+                .as_str();
+            answers.push(unescape(answer));
+        }
+    }
+    Fixtures { examples, answers }
+}
+
+impl Fixtures {
+    pub fn write(&self, directory: &Path) -> io::Result<Vec<PathBuf>> {
+        create_dir_all(directory)?;
+        let mut written = Vec::new();
+        for (index, example) in self.examples.iter().enumerate() {
+            let path = directory.join(format!("example-{}.txt", index + 1));
+            write(&path, example)?;
+            written.push(path);
+        }
+        for (index, answer) in self.answers.iter().enumerate() {
+            let path = directory.join(format!("answer-{}.txt", index + 1));
+            write(&path, format!("{}\n", answer))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{read_to_string, remove_dir_all};
+
+    const PAGE: &str = r#"<html><body><main>
+<article class="day-desc"><h2>--- Day 5: Supply Stacks ---</h2>
+<p>For example:</p>
+<pre><code>    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+</code></pre>
+<p>In this example, the top crates are <code>C</code> in stack 1, so you should give <code><em>CMZ</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>ABC</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>Using the same example:</p>
+<pre><code>    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+</code></pre>
+<pre><code>a &lt; b &amp;&amp; <em>c</em> &gt; d</code></pre>
+<p>The answer is <em><code>MCD</code></em>.</p>
+</article>
+</main></body></html>"#;
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            "a < b && c > \"d\" 'e'",
+            unescape("a &lt; b &amp;&amp; <em>c</em> &gt; &quot;d&quot; &#39;e&#39;")
+        );
+        assert_eq!("&lt;", unescape("&amp;lt;"));
+    }
+
+    #[test]
+    fn test_extract() {
+        let fixtures = extract(PAGE);
+        assert_eq!(
+            vec![
+                "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\n"
+                    .to_string(),
+                "a < b && c > d".to_string(),
+            ],
+            fixtures.examples
+        );
+        assert_eq!(vec!["CMZ".to_string(), "MCD".to_string()], fixtures.answers);
+    }
+
+    #[test]
+    fn test_extract_without_articles() {
+        let fixtures = extract("<pre><code>1\n2\n</code></pre><code><em>3</em></code>");
+        assert_eq!(vec!["1\n2\n".to_string()], fixtures.examples);
+        assert_eq!(vec!["3".to_string()], fixtures.answers);
+        assert_eq!(
+            Fixtures {
+                examples: vec![],
+                answers: vec![],
+            },
+            extract("<p>nothing here</p>")
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let directory = temp_dir().join(format!("aoc-fixtures-{}", std::process::id()));
+        let written = extract(PAGE).write(&directory).unwrap();
+        assert_eq!(
+            vec![
+                directory.join("example-1.txt"),
+                directory.join("example-2.txt"),
+                directory.join("answer-1.txt"),
+                directory.join("answer-2.txt"),
+            ],
+            written
+        );
+        assert_eq!(
+            "a < b && c > d",
+            read_to_string(directory.join("example-2.txt")).unwrap()
+        );
+        assert_eq!(
+            "MCD\n",
+            read_to_string(directory.join("answer-2.txt")).unwrap()
+        );
+        remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,454 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::table;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+// Puzzles unlock at midnight EST, which is 05:00 UTC
+const UNLOCK_HOUR_UTC: i64 = 5;
+const SECONDS_PER_DAY: i64 = 86400;
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub completion_day_level: HashMap<u32, HashMap<u8, Star>>,
+}
+
+impl Member {
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    fn star_timestamp(&self, day: u32, part: u8) -> Option<i64> {
+        self.completion_day_level
+            .get(&day)
+            .and_then(|parts| parts.get(&part))
+            .map(|star| star.get_star_ts)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Leaderboard {
+    pub event: String,
+    pub members: HashMap<String, Member>,
+}
+
+impl FromStr for Leaderboard {
+    type Err = serde_json::Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let leaderboard: Leaderboard = serde_json::from_str(input)?;
+        // Reject a bad event here so day_stats can rely on a numeric year
+        leaderboard.year().map_err(|_| {
+            serde::de::Error::custom(format!("invalid event year: {:?}", leaderboard.event))
+        })?;
+        Ok(leaderboard)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DayStats {
+    pub day: u32,
+    pub member: String,
+    pub part1: Option<i64>,
+    pub part2: Option<i64>,
+    pub delta: Option<i64>,
+    pub score: u32,
+    pub rank: usize,
+    pub rank_change: Option<i64>,
+}
+
+impl Leaderboard {
+    fn year(&self) -> Result<i32, ParseIntError> {
+        self.event.trim().parse::<i32>()
+    }
+
+    fn sorted_members(&self) -> Vec<&Member> {
+        let mut members = self.members.values().collect::<Vec<&Member>>();
+        members.sort_by_key(|member| member.id);
+        members
+    }
+
+    fn last_day(&self) -> u32 {
+        self.members
+            .values()
+            .flat_map(|member| member.completion_day_level.keys())
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Local scores award N points for the first star of a part, N - 1 for the
+    // second and so on, where N is the number of members
+    fn part_points(&self, members: &[&Member], day: u32, part: u8) -> Vec<u32> {
+        let mut finishers = members
+            .iter()
+            .enumerate()
+            .filter_map(|(index, member)| member.star_timestamp(day, part).map(|ts| (ts, index)))
+            .collect::<Vec<(i64, usize)>>();
+        finishers.sort();
+        let mut points = vec![0; members.len()];
+        for (position, (_, index)) in finishers.iter().enumerate() {
+            points[*index] = (members.len() - position) as u32;
+        }
+        points
+    }
+
+    // The fields are public, so the year is checked here as well as when parsing
+    pub fn day_stats(&self) -> Result<Vec<DayStats>, ParseIntError> {
+        let year = self.year()?;
+        let members = self.sorted_members();
+        let mut scores = vec![0; members.len()];
+        let mut previous_ranks: Option<Vec<usize>> = None;
+        let mut stats = Vec::new();
+        for day in 1..=self.last_day() {
+            for part in [1, 2] {
+                for (score, points) in scores.iter_mut().zip(self.part_points(&members, day, part))
+                {
+                    *score += points;
+                }
+            }
+            let ranks = scores
+                .iter()
+                .map(|score| 1 + scores.iter().filter(|other| *other > score).count())
+                .collect::<Vec<usize>>();
+            let unlock = unlock_timestamp(year, day);
+            let mut day_rows = Vec::new();
+            for (index, member) in members.iter().enumerate() {
+                let part1 = member.star_timestamp(day, 1).map(|ts| ts - unlock);
+                let part2 = member.star_timestamp(day, 2).map(|ts| ts - unlock);
+                let delta = match (part1, part2) {
+                    (Some(part1), Some(part2)) => Some(part2 - part1),
+                    _ => None,
+                };
+                let rank_change = previous_ranks
+                    .as_ref()
+                    .map(|previous| previous[index] as i64 - ranks[index] as i64);
+                day_rows.push(DayStats {
+                    day,
+                    member: member.display_name(),
+                    part1,
+                    part2,
+                    delta,
+                    score: scores[index],
+                    rank: ranks[index],
+                    rank_change,
+                });
+            }
+            day_rows.sort_by(|a, b| a.rank.cmp(&b.rank).then(a.member.cmp(&b.member)));
+            stats.append(&mut day_rows);
+            previous_ranks = Some(ranks);
+        }
+        Ok(stats)
+    }
+}
+
+// Howard Hinnant's days_from_civil, valid for the proleptic Gregorian calendar
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year } as i64;
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+pub fn unlock_timestamp(year: i32, day: u32) -> i64 {
+    days_from_civil(year, 12, day) * SECONDS_PER_DAY + UNLOCK_HOUR_UTC * 3600
+}
+
+pub fn format_duration(seconds: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+fn format_optional_duration(seconds: Option<i64>) -> String {
+    match seconds {
+        Some(seconds) => format_duration(seconds),
+        None => "-".to_string(),
+    }
+}
+
+fn format_rank_change(rank_change: Option<i64>) -> String {
+    match rank_change {
+        Some(change) if change > 0 => format!("+{}", change),
+        Some(change) if change < 0 => format!("{}", change),
+        Some(_) => "=".to_string(),
+        None => "".to_string(),
+    }
+}
+
+pub fn render_table(stats: &[DayStats]) -> String {
+    let rows = stats
+        .iter()
+        .map(|row| {
+            vec![
+                row.day.to_string(),
+                row.member.clone(),
+                format_optional_duration(row.part1),
+                format_optional_duration(row.part2),
+                format_optional_duration(row.delta),
+                row.score.to_string(),
+                row.rank.to_string(),
+                format_rank_change(row.rank_change),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    table::render_table(
+        &[
+            "Day", "Member", "Part 1", "Part 2", "Delta", "Score", "Rank", "Change",
+        ],
+        &rows,
+    )
+}
+
+fn format_optional_seconds(seconds: Option<i64>) -> String {
+    seconds
+        .map(|seconds| seconds.to_string())
+        .unwrap_or_default()
+}
+
+pub fn render_csv(stats: &[DayStats]) -> String {
+    let rows = stats
+        .iter()
+        .map(|row| {
+            vec![
+                row.day.to_string(),
+                row.member.clone(),
+                format_optional_seconds(row.part1),
+                format_optional_seconds(row.part2),
+                format_optional_seconds(row.delta),
+                row.score.to_string(),
+                row.rank.to_string(),
+                format_optional_seconds(row.rank_change),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    table::render_csv(
+        &[
+            "day",
+            "member",
+            "part1_seconds",
+            "part2_seconds",
+            "delta_seconds",
+            "score",
+            "rank",
+            "rank_change",
+        ],
+        &rows,
+    )
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEADERBOARD: &str = r#"{
+        "owner_id": 1,
+        "event": "2022",
+        "members": {
+            "1": {
+                "id": 1,
+                "name": "alice",
+                "stars": 3,
+                "local_score": 0,
+                "global_score": 0,
+                "last_star_ts": 0,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1669870900, "star_index": 0},
+                        "2": {"get_star_ts": 1669871000, "star_index": 1}
+                    },
+                    "2": {
+                        "1": {"get_star_ts": 1669958000, "star_index": 2}
+                    }
+                }
+            },
+            "2": {
+                "id": 2,
+                "name": null,
+                "stars": 4,
+                "local_score": 0,
+                "global_score": 0,
+                "last_star_ts": 0,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1669870850, "star_index": 0},
+                        "2": {"get_star_ts": 1669874400, "star_index": 1}
+                    },
+                    "2": {
+                        "1": {"get_star_ts": 1669957500, "star_index": 2},
+                        "2": {"get_star_ts": 1669957600, "star_index": 3}
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_leaderboard_from_str() {
+        let leaderboard = LEADERBOARD.parse::<Leaderboard>().unwrap();
+        assert_eq!("2022", leaderboard.event);
+        assert_eq!(2, leaderboard.members.len());
+        assert_eq!(
+            Some(1669871000),
+            leaderboard.members["1"].star_timestamp(1, 2)
+        );
+        assert_eq!(None, leaderboard.members["1"].star_timestamp(2, 2));
+        assert!("{}".parse::<Leaderboard>().is_err());
+        let bad_event = LEADERBOARD.replace(r#""event": "2022""#, r#""event": "next year""#);
+        let error = bad_event.parse::<Leaderboard>().unwrap_err();
+        assert!(error.to_string().contains("invalid event year"));
+    }
+
+    #[test]
+    fn test_day_stats_bad_event() {
+        let leaderboard = Leaderboard {
+            event: "next year".to_string(),
+            members: HashMap::new(),
+        };
+        assert!(leaderboard.day_stats().is_err());
+    }
+
+    #[test]
+    fn test_display_name() {
+        let leaderboard = LEADERBOARD.parse::<Leaderboard>().unwrap();
+        assert_eq!("alice", leaderboard.members["1"].display_name());
+        assert_eq!(
+            "(anonymous user #2)",
+            leaderboard.members["2"].display_name()
+        );
+    }
+
+    #[test]
+    fn test_unlock_timestamp() {
+        assert_eq!(1669870800, unlock_timestamp(2022, 1));
+        assert_eq!(1671771600, unlock_timestamp(2022, 23));
+        assert_eq!(1606798800, unlock_timestamp(2020, 1));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("00:00:00", format_duration(0));
+        assert_eq!("01:01:01", format_duration(3661));
+        assert_eq!("26:00:05", format_duration(93605));
+    }
+
+    #[test]
+    fn test_day_stats() {
+        let stats = LEADERBOARD
+            .parse::<Leaderboard>()
+            .unwrap()
+            .day_stats()
+            .unwrap();
+        assert_eq!(
+            vec![
+                DayStats {
+                    day: 1,
+                    member: "(anonymous user #2)".to_string(),
+                    part1: Some(50),
+                    part2: Some(3600),
+                    delta: Some(3550),
+                    score: 3,
+                    rank: 1,
+                    rank_change: None,
+                },
+                DayStats {
+                    day: 1,
+                    member: "alice".to_string(),
+                    part1: Some(100),
+                    part2: Some(200),
+                    delta: Some(100),
+                    score: 3,
+                    rank: 1,
+                    rank_change: None,
+                },
+                DayStats {
+                    day: 2,
+                    member: "(anonymous user #2)".to_string(),
+                    part1: Some(300),
+                    part2: Some(400),
+                    delta: Some(100),
+                    score: 7,
+                    rank: 1,
+                    rank_change: Some(0),
+                },
+                DayStats {
+                    day: 2,
+                    member: "alice".to_string(),
+                    part1: Some(800),
+                    part2: None,
+                    delta: None,
+                    score: 4,
+                    rank: 2,
+                    rank_change: Some(-1),
+                },
+            ],
+            stats
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let stats = LEADERBOARD
+            .parse::<Leaderboard>()
+            .unwrap()
+            .day_stats()
+            .unwrap();
+        let table = render_table(&stats);
+        let lines = table.lines().collect::<Vec<&str>>();
+        assert_eq!(5, lines.len());
+        assert!(lines[0].starts_with("Day  Member"));
+        assert_eq!(
+            "2    alice                00:13:20  -         -         4      2     -1",
+            lines[4]
+        );
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let stats = LEADERBOARD
+            .parse::<Leaderboard>()
+            .unwrap()
+            .day_stats()
+            .unwrap();
+        let csv = render_csv(&stats);
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(
+            "day,member,part1_seconds,part2_seconds,delta_seconds,score,rank,rank_change",
+            lines[0]
+        );
+        assert_eq!("1,(anonymous user #2),50,3600,3550,3,1,", lines[1]);
+        assert_eq!("2,alice,800,,,4,2,-1", lines[4]);
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use aoc::fixtures::extract;
+use aoc::leaderboard::{render_csv, render_table, Leaderboard};
+use aoc::runner::RunOptions;
+use std::env::args;
+use std::fs::read_to_string;
+use std::process::{exit, Command};
+
+const USAGE: &str = "Usage:
+    aoc run --day <day> [--variant <name|all>] [--profile-memory]
+    aoc explore --day <5|9|10|11> [--part <part>]
+    aoc fixtures --day <day> <saved puzzle page>
+    aoc leaderboard <file> [--csv]";
+
+#[cfg(not(tarpaulin_include))]
+fn main() {
+    let args = args().skip(1).collect::<Vec<String>>();
+    match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..], false),
+        Some("explore") => run(&args[1..], true),
+        Some("fixtures") => fixtures(&args[1..]),
+        Some("leaderboard") => leaderboard(&args[1..]),
+        _ => usage(),
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(1);
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run(args: &[String], explore: bool) {
+    let mut options = match RunOptions::from_args(args) {
+        Some(options) => options,
+        None => usage(),
+    };
+    options.explore = explore;
+    let status = Command::new("cargo")
+        .args(options.cargo_args())
+        .current_dir(options.day_directory())
+        .status()
+        .expect("Unable to run cargo");
+    exit(status.code().unwrap_or(1));
+}
+
+#[cfg(not(tarpaulin_include))]
+fn fixtures(args: &[String]) {
+    let options = match RunOptions::from_args(args) {
+        Some(options) => options,
+        None => usage(),
+    };
+    let path = match options.solution_args.first() {
+        Some(path) => path,
+        None => usage(),
+    };
+    let html = read_to_string(path).expect("Unable to read puzzle page");
+    let written = extract(&html)
+        .write(&options.day_directory().join("fixtures"))
+        .expect("Unable to write fixtures");
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn leaderboard(args: &[String]) {
+    let path = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(path) => path,
+        None => usage(),
+    };
+    let input = read_to_string(path).expect("Unable to read leaderboard file");
+    let leaderboard = input
+        .parse::<Leaderboard>()
+        .expect("Unable to parse leaderboard file");
+    let stats = leaderboard
+        .day_stats()
+        .expect("Leaderboard event is not a year");
+    if args.iter().any(|arg| "--csv" == arg) {
+        print!("{}", render_csv(&stats));
+    } else {
+        print!("{}", render_table(&stats));
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq)]
+pub struct RunOptions {
+    pub day: u8,
+    pub profile_memory: bool,
+    pub explore: bool,
+    pub solution_args: Vec<String>,
+}
+
+impl RunOptions {
+    pub fn from_args(args: &[String]) -> Option<RunOptions> {
+        let mut day = None;
+        let mut profile_memory = false;
+        let mut solution_args = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(value) = arg.strip_prefix("--day=") {
+                day = value.parse::<u8>().ok();
+            } else if "--day" == arg {
+                day = args.next().and_then(|value| value.parse::<u8>().ok());
+            } else if "--profile-memory" == arg {
+                profile_memory = true;
+            } else {
+                solution_args.push(arg.clone());
+            }
+        }
+        Some(RunOptions {
+            day: day.filter(|day| (1..=25).contains(day))?,
+            profile_memory,
+            explore: false,
+            solution_args,
+        })
+    }
+
+    pub fn day_directory(&self) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(format!("day-{:02}", self.day))
+    }
+
+    pub fn cargo_args(&self) -> Vec<String> {
+        let mut cargo_args = ["run", "--release", "--quiet"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>();
+        let mut features = Vec::new();
+        if self.profile_memory {
+            features.push("alloc-profile");
+        }
+        if self.explore {
+            features.push("explorer");
+        }
+        if !features.is_empty() {
+            cargo_args.push("--features".to_string());
+            cargo_args.push(features.join(","));
+        }
+        cargo_args.push("--".to_string());
+        if self.explore {
+            cargo_args.push("--explore".to_string());
+        }
+        cargo_args.extend(self.solution_args.iter().cloned());
+        cargo_args
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &[&str]) -> Vec<String> {
+        input.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_run_options_from_args() {
+        assert_eq!(
+            Some(RunOptions {
+                day: 6,
+                profile_memory: false,
+                explore: false,
+                solution_args: args(&["--variant", "all"]),
+            }),
+            RunOptions::from_args(&args(&["--day", "6", "--variant", "all"]))
+        );
+        assert_eq!(
+            Some(RunOptions {
+                day: 12,
+                profile_memory: true,
+                explore: false,
+                solution_args: vec![],
+            }),
+            RunOptions::from_args(&args(&["--day=12", "--profile-memory"]))
+        );
+        assert_eq!(None, RunOptions::from_args(&args(&["--variant", "all"])));
+        assert_eq!(None, RunOptions::from_args(&args(&["--day", "26"])));
+        assert_eq!(None, RunOptions::from_args(&args(&["--day", "six"])));
+    }
+
+    #[test]
+    fn test_day_directory() {
+        let options = RunOptions::from_args(&args(&["--day", "6"])).unwrap();
+        assert!(options.day_directory().ends_with("../day-06"));
+        assert!(options.day_directory().join("Cargo.toml").exists());
+    }
+
+    #[test]
+    fn test_cargo_args() {
+        let options = RunOptions::from_args(&args(&["--day", "6", "--variant", "all"])).unwrap();
+        assert_eq!(
+            args(&["run", "--release", "--quiet", "--", "--variant", "all"]),
+            options.cargo_args()
+        );
+        let options = RunOptions::from_args(&args(&["--day", "4", "--profile-memory"])).unwrap();
+        assert_eq!(
+            args(&[
+                "run",
+                "--release",
+                "--quiet",
+                "--features",
+                "alloc-profile",
+                "--"
+            ]),
+            options.cargo_args()
+        );
+        let mut options = RunOptions::from_args(&args(&["--day", "10", "--part", "2"])).unwrap();
+        options.explore = true;
+        assert_eq!(
+            args(&[
+                "run",
+                "--release",
+                "--quiet",
+                "--features",
+                "explorer",
+                "--",
+                "--explore",
+                "--part",
+                "2"
+            ]),
+            options.cargo_args()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Left-aligned columns separated by two spaces, with trailing padding trimmed
+pub fn render_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = header
+        .iter()
+        .map(|title| title.chars().count())
+        .collect::<Vec<usize>>();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut output = String::new();
+    let header = header.iter().map(|title| title.to_string()).collect();
+    for row in [header].iter().chain(rows.iter()) {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ");
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+pub fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn render_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut output = String::new();
+    let header = header.iter().map(|title| title.to_string()).collect();
+    for row in [header].iter().chain(rows.iter()) {
+        let line = row
+            .iter()
+            .map(|field| escape_csv(field))
+            .collect::<Vec<String>>()
+            .join(",");
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec!["1".to_string(), "Rock, Hard".to_string()],
+            vec!["12".to_string(), String::new()],
+        ]
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            "#   Name
+1   Rock, Hard
+12
+",
+            render_table(&["#", "Name"], &rows())
+        );
+        assert_eq!("A  B\n", render_table(&["A", "B"], &[]));
+    }
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!("plain", escape_csv("plain"));
+        assert_eq!("\"a,\"\"b\"\"\"", escape_csv("a,\"b\""));
+        assert_eq!("\"two\nlines\"", escape_csv("two\nlines"));
+    }
+
+    #[test]
+    fn test_render_csv() {
+        assert_eq!(
+            "#,Name
+1,\"Rock, Hard\"
+12,
+",
+            render_csv(&["#", "Name"], &rows())
+        );
+    }
+}
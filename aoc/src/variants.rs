@@ -0,0 +1,326 @@
+// Copyright 2023 CJ Harries
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::allocator::{self, AllocationStats};
+use std::fmt;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+pub type Solver = fn(&str) -> String;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Selection {
+    Default,
+    All,
+    Named(String),
+}
+
+impl Selection {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Selection {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--variant=") {
+                Some(value) => Some(value.to_string()),
+                None if "--variant" == arg => args.next(),
+                None => continue,
+            };
+            return match value.as_deref() {
+                Some("all") => Selection::All,
+                Some(name) => Selection::Named(name.to_string()),
+                None => Selection::Default,
+            };
+        }
+        Selection::Default
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Variant {
+    pub part: u8,
+    pub name: &'static str,
+    pub solver: Solver,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Timing {
+    pub name: &'static str,
+    pub answer: String,
+    pub elapsed: Duration,
+    pub allocations: Option<AllocationStats>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PartReport {
+    pub part: u8,
+    pub timings: Vec<Timing>,
+}
+
+impl PartReport {
+    pub fn agrees(&self) -> bool {
+        self.timings
+            .windows(2)
+            .all(|pair| pair[0].answer == pair[1].answer)
+    }
+}
+
+impl fmt::Display for PartReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if 1 == self.timings.len() {
+            write!(f, "Part {}: {}", self.part, self.timings[0].answer)?;
+            if let Some(allocations) = self.timings[0].allocations {
+                write!(f, " ({})", allocations)?;
+            }
+            return Ok(());
+        }
+        writeln!(f, "Part {}:", self.part)?;
+        let name_width = self
+            .timings
+            .iter()
+            .map(|timing| timing.name.len())
+            .max()
+            .unwrap_or(0);
+        let answer_width = self
+            .timings
+            .iter()
+            .map(|timing| timing.answer.len())
+            .max()
+            .unwrap_or(0);
+        for timing in self.timings.iter() {
+            write!(
+                f,
+                "  {:<name_width$}  {:<answer_width$}  {:?}",
+                timing.name,
+                timing.answer,
+                timing.elapsed,
+                name_width = name_width,
+                answer_width = answer_width
+            )?;
+            match timing.allocations {
+                Some(allocations) => writeln!(f, "  {}", allocations)?,
+                None => writeln!(f)?,
+            }
+        }
+        if self.agrees() {
+            write!(f, "  all variants agree")
+        } else {
+            write!(f, "  variants disagree")
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Variants(Vec<Variant>);
+
+impl Variants {
+    pub fn new() -> Variants {
+        Variants::default()
+    }
+
+    pub fn register(mut self, part: u8, name: &'static str, solver: Solver) -> Variants {
+        self.0.push(Variant { part, name, solver });
+        self
+    }
+
+    fn parts(&self) -> Vec<u8> {
+        let mut parts = self
+            .0
+            .iter()
+            .map(|variant| variant.part)
+            .collect::<Vec<u8>>();
+        parts.sort();
+        parts.dedup();
+        parts
+    }
+
+    // The first variant registered for a part is its default
+    pub fn select(&self, part: u8, selection: &Selection) -> Vec<Variant> {
+        let candidates = self.0.iter().filter(|variant| part == variant.part);
+        match selection {
+            Selection::Default => candidates.take(1).copied().collect(),
+            Selection::All => candidates.copied().collect(),
+            Selection::Named(name) => candidates
+                .filter(|variant| name == variant.name)
+                .copied()
+                .collect(),
+        }
+    }
+
+    pub fn run(&self, input: &str, selection: &Selection) -> Vec<PartReport> {
+        self.parts()
+            .into_iter()
+            .map(|part| PartReport {
+                part,
+                timings: self
+                    .select(part, selection)
+                    .into_iter()
+                    .map(|variant| {
+                        allocator::reset();
+                        let start = Instant::now();
+                        let answer = (variant.solver)(input);
+                        let elapsed = start.elapsed();
+                        Timing {
+                            name: variant.name,
+                            answer,
+                            elapsed,
+                            allocations: allocator::stats(),
+                        }
+                    })
+                    .collect(),
+            })
+            .filter(|report| !report.timings.is_empty())
+            .collect()
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    pub fn run_from_args(&self, input: &str) {
+        let selection = Selection::from_args(std::env::args().skip(1));
+        let reports = self.run(input, &selection);
+        if reports.is_empty() {
+            eprintln!("No variant matches {:?}", selection);
+            exit(1);
+        }
+        for report in reports.iter() {
+            println!("{}", report);
+        }
+        if !reports.iter().all(PartReport::agrees) {
+            exit(1);
+        }
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variants() -> Variants {
+        Variants::new()
+            .register(1, "length", |input| input.len().to_string())
+            .register(1, "chars", |input| input.chars().count().to_string())
+            .register(2, "upper", |input| input.to_uppercase())
+            .register(2, "wrong", |input| input.to_string())
+    }
+
+    fn args(input: &[&str]) -> Vec<String> {
+        input.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_selection_from_args() {
+        assert_eq!(Selection::Default, Selection::from_args(args(&[])));
+        assert_eq!(
+            Selection::Default,
+            Selection::from_args(args(&["--variant"]))
+        );
+        assert_eq!(
+            Selection::All,
+            Selection::from_args(args(&["--variant", "all"]))
+        );
+        assert_eq!(
+            Selection::Named("chars".to_string()),
+            Selection::from_args(args(&["--release", "--variant=chars"]))
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        let variants = variants();
+        let names = |part, selection| {
+            variants
+                .select(part, &selection)
+                .iter()
+                .map(|variant| variant.name)
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(vec!["length"], names(1, Selection::Default));
+        assert_eq!(vec!["length", "chars"], names(1, Selection::All));
+        assert_eq!(
+            vec!["upper"],
+            names(2, Selection::Named("upper".to_string()))
+        );
+        assert!(names(3, Selection::All).is_empty());
+    }
+
+    #[test]
+    fn test_run() {
+        let reports = variants().run("abc", &Selection::All);
+        assert_eq!(2, reports.len());
+        assert_eq!(1, reports[0].part);
+        assert_eq!(
+            vec!["3", "3"],
+            reports[0]
+                .timings
+                .iter()
+                .map(|timing| timing.answer.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert!(reports[0].agrees());
+        assert!(!reports[1].agrees());
+        let reports = variants().run("abc", &Selection::Named("chars".to_string()));
+        assert_eq!(1, reports.len());
+        assert_eq!("chars", reports[0].timings[0].name);
+    }
+
+    #[test]
+    fn test_display_single_part_report() {
+        let mut report = PartReport {
+            part: 1,
+            timings: vec![Timing {
+                name: "length",
+                answer: "3".to_string(),
+                elapsed: Duration::from_micros(5),
+                allocations: None,
+            }],
+        };
+        assert_eq!("Part 1: 3", report.to_string());
+        report.timings[0].allocations = Some(AllocationStats {
+            peak_bytes: 0,
+            allocations: 0,
+            allocated_bytes: 0,
+        });
+        assert_eq!(
+            "Part 1: 3 (peak 0 B, 0 allocations, 0 B allocated)",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn test_display_part_report() {
+        let report = PartReport {
+            part: 2,
+            timings: vec![
+                Timing {
+                    name: "upper",
+                    answer: "ABC".to_string(),
+                    elapsed: Duration::from_micros(5),
+                    allocations: None,
+                },
+                Timing {
+                    name: "wrong",
+                    answer: "abc".to_string(),
+                    elapsed: Duration::from_micros(10),
+                    allocations: Some(AllocationStats {
+                        peak_bytes: 3,
+                        allocations: 1,
+                        allocated_bytes: 3,
+                    }),
+                },
+            ],
+        };
+        assert_eq!(
+            "Part 2:\n  upper  ABC  5µs\n  wrong  abc  10µs  peak 3 B, 1 allocations, 3 B allocated\n  variants disagree",
+            report.to_string()
+        );
+    }
+}
@@ -12,57 +12,344 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::fs::read_to_string;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::env::args;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::RangeInclusive;
+use std::process::exit;
+use std::str::FromStr;
+
+const INPUT_FILE: &str = "input-1.txt";
+
+#[cfg(not(tarpaulin_include))]
+fn open_input() -> BufReader<File> {
+    BufReader::new(File::open(INPUT_FILE).expect("Unable to read input file"))
+}
+
+#[cfg(not(tarpaulin_include))]
+fn or_exit<T>(result: Result<T, CalorieError>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        exit(1);
+    })
+}
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
-    let input = read_to_string("input-1.txt").expect("Unable to read input file");
-    println!("Max single elf: {}", find_max_elf(input.as_str()));
-    println!("Max three elves: {}", find_max_elves(input.as_str()));
-}
-
-fn find_max_elf(input: &str) -> u32 {
-    let mut max_elf = 0;
-    let mut current_elf = 0;
-    for line in input.lines() {
-        if line.is_empty() {
-            if current_elf > max_elf {
-                max_elf = current_elf;
+    let args = args().collect::<Vec<String>>();
+    let top = args
+        .iter()
+        .position(|arg| "--top" == arg)
+        .and_then(|index| args.get(index + 1))
+        .and_then(|n| n.parse::<usize>().ok());
+    let count = top.unwrap_or(0).max(3);
+    // The inventory keeps every elf in memory, so it is opt-in for huge inputs
+    let inventory = args
+        .iter()
+        .any(|arg| "--stats" == arg)
+        .then(|| or_exit(Inventory::from_reader(open_input())));
+    let totals = or_exit(largest_totals(inventory.as_ref(), open_input, count));
+    let max = or_exit(find_max_elf(&totals));
+    println!("Max single elf: {}", max);
+    println!("Max three elves: {}", or_exit(find_max_elves(&totals)));
+    if let Some(n) = top {
+        println!("Max {} elves: {}", n, or_exit(sum_top_n(&totals, n)));
+    }
+    let inventory = match inventory {
+        Some(inventory) => inventory,
+        None => return,
+    };
+    let carriers = inventory
+        .argmax()
+        .iter()
+        .map(|elf| format!("#{}", elf.index))
+        .collect::<Vec<String>>();
+    println!("Carried by elf: {}", carriers.join(", "));
+    let runners_up = inventory
+        .in_range(max.saturating_sub(1000)..=max)
+        .iter()
+        .map(|elf| format!("#{}", elf.index))
+        .collect::<Vec<String>>();
+    println!("Within 1000 of the max: {}", runners_up.join(", "));
+    if let (Some(mean), Some(median), Some(p90)) = (
+        inventory.mean(),
+        inventory.median(),
+        inventory.percentile(90.0),
+    ) {
+        println!("Mean: {:.1}, median: {:.1}, p90: {:.1}", mean, median, p90);
+    }
+    for (bucket, count) in inventory.histogram(10000).unwrap_or_default() {
+        println!("{:>6}: {}", bucket, "#".repeat(count));
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum CalorieError {
+    Io(io::ErrorKind),
+    InvalidCalories { line: usize, value: String },
+    ElfOverflow { elf: usize },
+    SumOverflow,
+}
+
+impl fmt::Display for CalorieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalorieError::Io(kind) => write!(f, "Unable to read input: {}", kind),
+            CalorieError::InvalidCalories { line, value } => {
+                write!(f, "Line {}: {:?} is not a calorie count", line, value)
             }
-            current_elf = 0;
-        } else {
-            let line_elf = line.trim().parse::<u32>().expect("Unable to parse line");
-            current_elf += line_elf;
+            CalorieError::ElfOverflow { elf } => {
+                write!(f, "Elf #{} carries more calories than fit in a u64", elf)
+            }
+            CalorieError::SumOverflow => write!(f, "Sum of totals does not fit in a u64"),
         }
     }
-    if current_elf > max_elf {
-        max_elf = current_elf;
+}
+
+// Streams one elf at a time, so memory only grows with the largest elf
+struct ElfItems<R: BufRead> {
+    reader: R,
+    line: String,
+    line_number: usize,
+}
+
+impl<R: BufRead> ElfItems<R> {
+    fn new(reader: R) -> ElfItems<R> {
+        ElfItems {
+            reader,
+            line: String::new(),
+            line_number: 0,
+        }
     }
-    max_elf
 }
 
-fn find_max_elves(input: &str) -> u32 {
-    let mut current_elf = 0;
-    let mut elves = Vec::new();
-    for line in input.lines() {
-        if line.is_empty() {
-            elves.push(current_elf);
-            current_elf = 0;
+impl<R: BufRead> Iterator for ElfItems<R> {
+    type Item = Result<Vec<u64>, CalorieError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut items = Vec::new();
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => break,
+                Ok(_) => self.line_number += 1,
+                Err(error) => return Some(Err(CalorieError::Io(error.kind()))),
+            }
+            let line = self.line.trim();
+            if line.is_empty() {
+                if !items.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            match line.parse::<u64>() {
+                Ok(calories) => items.push(calories),
+                Err(_) => {
+                    return Some(Err(CalorieError::InvalidCalories {
+                        line: self.line_number,
+                        value: line.to_string(),
+                    }))
+                }
+            }
+        }
+        if items.is_empty() {
+            None
         } else {
-            let line_elf = line.trim().parse::<u32>().expect("Unable to parse line");
-            current_elf += line_elf;
+            Some(Ok(items))
+        }
+    }
+}
+
+fn checked_total(items: &[u64], elf: usize) -> Result<u64, CalorieError> {
+    items
+        .iter()
+        .try_fold(0u64, |total, calories| total.checked_add(*calories))
+        .ok_or(CalorieError::ElfOverflow { elf })
+}
+
+fn elf_totals(input: impl BufRead) -> impl Iterator<Item = Result<u64, CalorieError>> {
+    ElfItems::new(input)
+        .enumerate()
+        .map(|(index, items)| checked_total(&items?, index + 1))
+}
+
+fn top_n<T: Ord>(totals: impl IntoIterator<Item = T>, n: usize) -> Vec<T> {
+    let mut heap = BinaryHeap::with_capacity(n + 1);
+    for total in totals {
+        heap.push(Reverse(total));
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(total)| total)
+        .collect()
+}
+
+fn try_top_n<T: Ord, E>(
+    totals: impl IntoIterator<Item = Result<T, E>>,
+    n: usize,
+) -> Result<Vec<T>, E> {
+    let mut error = None;
+    let top = top_n(
+        totals.into_iter().map_while(|total| match total {
+            Ok(total) => Some(total),
+            Err(total_error) => {
+                error = Some(total_error);
+                None
+            }
+        }),
+        n,
+    );
+    match error {
+        Some(error) => Err(error),
+        None => Ok(top),
+    }
+}
+
+// Largest first, read in one pass holding at most n totals
+fn top_totals(input: impl BufRead, n: usize) -> Result<Vec<u64>, CalorieError> {
+    try_top_n(elf_totals(input), n)
+}
+
+// A loaded inventory already holds every total, so the input is only opened to
+// stream it when there is none
+fn largest_totals<R: BufRead>(
+    inventory: Option<&Inventory>,
+    open: impl FnOnce() -> R,
+    n: usize,
+) -> Result<Vec<u64>, CalorieError> {
+    match inventory {
+        Some(inventory) => Ok(top_n(inventory.0.iter().map(Elf::total), n)),
+        None => top_totals(open(), n),
+    }
+}
+
+// Takes totals from top_totals, so one read can answer every part
+fn sum_top_n(top: &[u64], n: usize) -> Result<u64, CalorieError> {
+    top.iter()
+        .take(n)
+        .try_fold(0u64, |sum, total| sum.checked_add(*total))
+        .ok_or(CalorieError::SumOverflow)
+}
+
+fn find_max_elf(top: &[u64]) -> Result<u64, CalorieError> {
+    sum_top_n(top, 1)
+}
+
+fn find_max_elves(top: &[u64]) -> Result<u64, CalorieError> {
+    sum_top_n(top, 3)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Elf {
+    index: usize,
+    items: Vec<u64>,
+}
+
+impl Elf {
+    // Totals are checked for overflow when the inventory is parsed
+    fn total(&self) -> u64 {
+        self.items.iter().sum()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Inventory(Vec<Elf>);
+
+impl FromStr for Inventory {
+    type Err = CalorieError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Inventory::from_reader(input.as_bytes())
+    }
+}
+
+impl Inventory {
+    fn from_reader(input: impl BufRead) -> Result<Inventory, CalorieError> {
+        let mut elves = Vec::new();
+        for (index, items) in ElfItems::new(input).enumerate() {
+            let items = items?;
+            checked_total(&items, index + 1)?;
+            elves.push(Elf {
+                index: index + 1,
+                items,
+            });
         }
+        Ok(Inventory(elves))
     }
-    if current_elf > 0 {
-        elves.push(current_elf);
+
+    fn sorted_totals(&self) -> Vec<u64> {
+        let mut totals = self.0.iter().map(Elf::total).collect::<Vec<u64>>();
+        totals.sort();
+        totals
     }
-    elves.sort();
-    elves.reverse();
-    let mut max_elf_sum: u32 = 0;
-    for elf in &elves[0..3] {
-        max_elf_sum += *elf;
+
+    fn argmax(&self) -> Vec<&Elf> {
+        self.top_n(1)
+    }
+
+    // Elves tied with the nth largest total are all included
+    fn top_n(&self, n: usize) -> Vec<&Elf> {
+        let threshold = match top_n(self.0.iter().map(Elf::total), n).last() {
+            Some(threshold) => *threshold,
+            None => return vec![],
+        };
+        let mut elves = self
+            .0
+            .iter()
+            .filter(|elf| elf.total() >= threshold)
+            .collect::<Vec<&Elf>>();
+        elves.sort_by(|a, b| b.total().cmp(&a.total()).then(a.index.cmp(&b.index)));
+        elves
+    }
+
+    fn mean(&self) -> Option<f64> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let sum = self.0.iter().map(|elf| elf.total() as f64).sum::<f64>();
+        Some(sum / self.0.len() as f64)
+    }
+
+    fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+
+    // Linear interpolation between the closest ranks
+    fn percentile(&self, percentile: f64) -> Option<f64> {
+        if self.0.is_empty() || !(0.0..=100.0).contains(&percentile) {
+            return None;
+        }
+        let totals = self.sorted_totals();
+        let rank = percentile / 100.0 * (totals.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        let fraction = rank - lower as f64;
+        Some(totals[lower] as f64 + (totals[upper] as f64 - totals[lower] as f64) * fraction)
+    }
+
+    fn histogram(&self, bucket_width: u64) -> Option<BTreeMap<u64, usize>> {
+        if 0 == bucket_width {
+            return None;
+        }
+        let mut histogram = BTreeMap::new();
+        for elf in self.0.iter() {
+            let bucket = elf.total() / bucket_width * bucket_width;
+            *histogram.entry(bucket).or_insert(0) += 1;
+        }
+        Some(histogram)
+    }
+
+    fn in_range(&self, range: RangeInclusive<u64>) -> Vec<&Elf> {
+        self.0
+            .iter()
+            .filter(|elf| range.contains(&elf.total()))
+            .collect()
     }
-    max_elf_sum
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -73,8 +360,8 @@ mod tests {
     #[test]
     fn test_find_max_elf() {
         assert_eq!(
-            24000,
-            find_max_elf(
+            Ok(24000),
+            top_totals(
                 "1000
         2000
         3000
@@ -89,11 +376,14 @@ mod tests {
         9000
 
         10000"
+                    .as_bytes(),
+                3
             )
+            .and_then(|top| find_max_elf(&top))
         );
         assert_eq!(
-            24000,
-            find_max_elf(
+            Ok(24000),
+            top_totals(
                 "1000
         2000
         3000
@@ -106,15 +396,18 @@ mod tests {
         7000
         8000
         9000"
+                    .as_bytes(),
+                3
             )
+            .and_then(|top| find_max_elf(&top))
         );
     }
 
     #[test]
     fn test_find_max_elves() {
         assert_eq!(
-            45000,
-            find_max_elves(
+            Ok(45000),
+            top_totals(
                 "1000
         2000
         3000
@@ -129,7 +422,243 @@ mod tests {
         9000
 
         10000"
+                    .as_bytes(),
+                3
+            )
+            .and_then(|top| find_max_elves(&top))
+        );
+    }
+
+    #[test]
+    fn test_elf_totals() {
+        assert_eq!(
+            Ok(vec![6000, 4000, 11000]),
+            elf_totals(
+                "
+                1000
+                2000
+                3000
+
+                4000
+
+
+                5000
+                6000
+                "
+                .as_bytes()
+            )
+            .collect::<Result<Vec<u64>, CalorieError>>()
+        );
+        assert_eq!(0, elf_totals("".as_bytes()).count());
+    }
+
+    #[test]
+    fn test_top_n() {
+        let totals = vec![6000, 4000, 11000, 24000, 10000];
+        assert_eq!(vec![24000, 11000], top_n(totals.clone(), 2));
+        assert_eq!(
+            vec![24000, 11000, 10000, 6000, 4000],
+            top_n(totals.clone(), 10)
+        );
+        assert!(top_n(totals, 0).is_empty());
+        assert_eq!(vec![5, 5], top_n(vec![5, 1, 5], 2));
+    }
+
+    #[test]
+    fn test_sum_top_n() {
+        let top = vec![24000, 11000, 10000];
+        assert_eq!(Ok(24000), find_max_elf(&top));
+        assert_eq!(Ok(45000), find_max_elves(&top));
+        assert_eq!(Ok(35000), sum_top_n(&top, 2));
+        assert_eq!(Ok(45000), sum_top_n(&top, 10));
+        assert_eq!(Ok(0), find_max_elf(&[]));
+        assert_eq!(
+            Err(CalorieError::SumOverflow),
+            find_max_elves(&[u64::MAX, 1])
+        );
+    }
+
+    #[test]
+    fn test_largest_totals() {
+        let inventory = INVENTORY.parse::<Inventory>().unwrap();
+        let unopened = || -> &[u8] { panic!("the inventory should be used instead") };
+        assert_eq!(
+            Ok(vec![24000, 11000, 10000]),
+            largest_totals(Some(&inventory), unopened, 3)
+        );
+        assert_eq!(
+            Ok(vec![24000, 11000, 10000]),
+            largest_totals(None, || INVENTORY.as_bytes(), 3)
+        );
+        assert_eq!(
+            Err(CalorieError::InvalidCalories {
+                line: 1,
+                value: "x".to_string(),
+            }),
+            largest_totals(None, || "x".as_bytes(), 3)
+        );
+    }
+
+    #[test]
+    fn test_find_max_elves_with_fewer_elves() {
+        assert_eq!(
+            Ok(3000),
+            top_totals(
+                "1000
+
+                2000"
+                    .as_bytes(),
+                3
             )
+            .and_then(|top| find_max_elves(&top))
+        );
+        assert_eq!(
+            Ok(0),
+            top_totals("".as_bytes(), 3).and_then(|top| find_max_elves(&top))
+        );
+    }
+
+    const INVENTORY: &str = "1000
+        2000
+        3000
+
+        4000
+
+        5000
+        6000
+
+        7000
+        8000
+        9000
+
+        10000";
+
+    #[test]
+    fn test_elf_items() {
+        assert_eq!(
+            Ok(vec![vec![1000, 2000], vec![3000]]),
+            ElfItems::new("1000\n2000\n\n\n3000\n".as_bytes())
+                .collect::<Result<Vec<Vec<u64>>, CalorieError>>()
+        );
+        assert_eq!(
+            Some(Err(CalorieError::InvalidCalories {
+                line: 3,
+                value: "12a".to_string(),
+            })),
+            ElfItems::new("1\n\n12a\n".as_bytes()).nth(1)
+        );
+    }
+
+    #[test]
+    fn test_inventory_from_str() {
+        let inventory = INVENTORY.parse::<Inventory>().unwrap();
+        assert_eq!(5, inventory.0.len());
+        assert_eq!(
+            Elf {
+                index: 3,
+                items: vec![5000, 6000],
+            },
+            inventory.0[2]
+        );
+        assert_eq!(11000, inventory.0[2].total());
+    }
+
+    #[test]
+    fn test_inventory_argmax_and_top_n() {
+        let inventory = INVENTORY.parse::<Inventory>().unwrap();
+        let indices = |elves: Vec<&Elf>| elves.iter().map(|elf| elf.index).collect::<Vec<usize>>();
+        assert_eq!(vec![4], indices(inventory.argmax()));
+        assert_eq!(vec![4, 3, 5], indices(inventory.top_n(3)));
+        assert_eq!(vec![4, 3, 5, 1, 2], indices(inventory.top_n(10)));
+        assert!(inventory.top_n(0).is_empty());
+        let tied = "10\n\n20\n\n5\n15\n\n20".parse::<Inventory>().unwrap();
+        assert_eq!(vec![2, 3, 4], indices(tied.argmax()));
+        assert_eq!(vec![2, 3, 4], indices(tied.top_n(2)));
+    }
+
+    #[test]
+    fn test_inventory_statistics() {
+        let inventory = INVENTORY.parse::<Inventory>().unwrap();
+        assert_eq!(Some(11000.0), inventory.mean());
+        assert_eq!(Some(10000.0), inventory.median());
+        assert_eq!(Some(4000.0), inventory.percentile(0.0));
+        assert_eq!(Some(24000.0), inventory.percentile(100.0));
+        assert_eq!(Some(5000.0), inventory.percentile(12.5));
+        assert_eq!(None, inventory.percentile(101.0));
+        let empty = "".parse::<Inventory>().unwrap();
+        assert_eq!(None, empty.mean());
+        assert_eq!(None, empty.median());
+    }
+
+    #[test]
+    fn test_inventory_histogram() {
+        let inventory = INVENTORY.parse::<Inventory>().unwrap();
+        let expected = BTreeMap::from([(0, 2), (10000, 2), (20000, 1)]);
+        assert_eq!(Some(expected), inventory.histogram(10000));
+        assert_eq!(None, inventory.histogram(0));
+    }
+
+    #[test]
+    fn test_inventory_in_range() {
+        let inventory = INVENTORY.parse::<Inventory>().unwrap();
+        let indices = inventory
+            .in_range(6000..=11000)
+            .iter()
+            .map(|elf| elf.index)
+            .collect::<Vec<usize>>();
+        assert_eq!(vec![1, 3, 5], indices);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let input = format!("{}\n1\n\n2", u64::MAX);
+        assert_eq!(
+            Err(CalorieError::ElfOverflow { elf: 1 }),
+            top_totals(input.as_bytes(), 3).and_then(|top| find_max_elf(&top))
+        );
+        assert_eq!(
+            Err(CalorieError::ElfOverflow { elf: 1 }),
+            input.parse::<Inventory>()
+        );
+        let input = format!("{}\n\n1", u64::MAX);
+        assert_eq!(
+            Ok(u64::MAX),
+            top_totals(input.as_bytes(), 3).and_then(|top| find_max_elf(&top))
+        );
+        assert_eq!(
+            Err(CalorieError::SumOverflow),
+            top_totals(input.as_bytes(), 3).and_then(|top| find_max_elves(&top))
+        );
+        let wrapping_u32 = format!("{}\n{}", u32::MAX, u32::MAX);
+        assert_eq!(
+            Ok(2 * u32::MAX as u64),
+            top_totals(wrapping_u32.as_bytes(), 3).and_then(|top| find_max_elf(&top))
+        );
+    }
+
+    #[test]
+    fn test_try_top_n() {
+        assert_eq!(
+            Ok(vec![3, 2]),
+            try_top_n(vec![Ok::<u32, ()>(1), Ok(3), Ok(2)], 2)
+        );
+        assert_eq!(Err("bad"), try_top_n(vec![Ok(1), Err("bad"), Ok(2)], 2));
+        assert_eq!(
+            Err(CalorieError::InvalidCalories {
+                line: 2,
+                value: "x".to_string(),
+            }),
+            top_totals("1\nx".as_bytes(), 3).and_then(|top| find_max_elves(&top))
+        );
+    }
+
+    #[test]
+    fn test_sum_top_n_streams_many_elves() {
+        let input = "7\n\n".repeat(100000);
+        let reader = BufReader::with_capacity(16, input.as_bytes());
+        assert_eq!(
+            Ok(70),
+            top_totals(reader, 10).and_then(|top| sum_top_n(&top, 10))
         );
     }
 }
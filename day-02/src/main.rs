@@ -12,119 +12,678 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use aoc::table;
+use serde::Deserialize;
+use std::env::args;
+use std::fmt;
 use std::fs::read_to_string;
+use std::process::exit;
+use std::str::FromStr;
 
-enum ChoiceScore {
-    Rock = 1,
-    Paper = 2,
-    Scissors = 3,
-}
+// Shapes come from the rule set, so a shape is its position in RuleSet::shapes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Shape(usize);
 
-enum ResultScore {
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Outcome {
     Loss = 0,
     Draw = 3,
     Win = 6,
 }
 
-lazy_static! {
-    // A,X = Rock
-    // B,Y = Paper
-    // C,Z = Scissors
-    static ref SCORING_MAP: HashMap<char, HashMap<char, u32>> = {
-        let mut map = HashMap::new();
-        map.insert('A', {
-            let mut inner_map = HashMap::new();
-            inner_map.insert('X', ResultScore::Draw as u32 + ChoiceScore::Rock as u32);
-            inner_map.insert('Y', ResultScore::Win as u32 + ChoiceScore::Paper as u32);
-            inner_map.insert('Z', ResultScore::Loss as u32 + ChoiceScore::Scissors as u32);
-            inner_map
-        });
-        map.insert('B', {
-            let mut inner_map = HashMap::new();
-            inner_map.insert('X', ResultScore::Loss as u32 + ChoiceScore::Rock as u32);
-            inner_map.insert('Y', ResultScore::Draw as u32 + ChoiceScore::Paper as u32);
-            inner_map.insert('Z', ResultScore::Win as u32 + ChoiceScore::Scissors as u32);
-            inner_map
-        });
-        map.insert('C', {
-            let mut inner_map = HashMap::new();
-            inner_map.insert('X', ResultScore::Win as u32 + ChoiceScore::Rock as u32);
-            inner_map.insert('Y', ResultScore::Loss as u32 + ChoiceScore::Paper as u32);
-            inner_map.insert('Z', ResultScore::Draw as u32 + ChoiceScore::Scissors as u32);
-            inner_map
-        });
-        map
+const OUTCOMES: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+struct ShapeRule {
+    name: String,
+    score: u32,
+    opponent: String,
+    player: String,
+    #[serde(default)]
+    beats: Vec<String>,
+}
+
+impl ShapeRule {
+    fn new(name: &str, score: u32, opponent: &str, player: &str, beats: &[&str]) -> ShapeRule {
+        ShapeRule {
+            name: name.to_string(),
+            score,
+            opponent: opponent.to_string(),
+            player: player.to_string(),
+            beats: beats.iter().map(|shape| shape.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+struct OutcomeLetters {
+    loss: String,
+    draw: String,
+    win: String,
+}
+
+impl Default for OutcomeLetters {
+    fn default() -> Self {
+        OutcomeLetters {
+            loss: "X".to_string(),
+            draw: "Y".to_string(),
+            win: "Z".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleSetConfig {
+    shapes: Vec<ShapeRule>,
+    #[serde(default)]
+    outcomes: OutcomeLetters,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum RuleSetError {
+    Toml(String),
+    NoShapes,
+    UnknownShape(String),
+    DuplicateName(String),
+    DuplicateLetter(String),
+    BeatsItself(String),
+    BeatEachOther(String, String),
+    NoShapeForOutcome(String, Outcome),
+}
+
+impl fmt::Display for RuleSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleSetError::Toml(error) => write!(f, "Invalid rule set: {}", error),
+            RuleSetError::NoShapes => write!(f, "A rule set needs at least one shape"),
+            RuleSetError::UnknownShape(name) => write!(f, "Unknown shape {}", name),
+            RuleSetError::DuplicateName(name) => write!(f, "Shape {} is defined twice", name),
+            RuleSetError::DuplicateLetter(letter) => {
+                write!(f, "Letter {} is used more than once in a column", letter)
+            }
+            RuleSetError::BeatsItself(name) => write!(f, "{} cannot beat itself", name),
+            RuleSetError::BeatEachOther(first, second) => {
+                write!(f, "{} and {} cannot beat each other", first, second)
+            }
+            RuleSetError::NoShapeForOutcome(opponent, outcome) => {
+                write!(f, "No shape gives a {:?} against {}", outcome, opponent)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct RuleSet {
+    shapes: Vec<ShapeRule>,
+    outcomes: OutcomeLetters,
+    wins: Vec<Vec<bool>>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::rps()
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = RuleSetError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let config = toml::from_str::<RuleSetConfig>(input)
+            .map_err(|error| RuleSetError::Toml(error.message().to_string()))?;
+        RuleSet::new(config.shapes, config.outcomes)
+    }
+}
+
+fn find_duplicate<'a>(values: impl Iterator<Item = &'a String>) -> Option<String> {
+    let mut seen = Vec::new();
+    for value in values {
+        if seen.contains(&value) {
+            return Some(value.clone());
+        }
+        seen.push(value);
+    }
+    None
+}
+
+impl RuleSet {
+    fn new(shapes: Vec<ShapeRule>, outcomes: OutcomeLetters) -> Result<RuleSet, RuleSetError> {
+        if shapes.is_empty() {
+            return Err(RuleSetError::NoShapes);
+        }
+        if let Some(name) = find_duplicate(shapes.iter().map(|shape| &shape.name)) {
+            return Err(RuleSetError::DuplicateName(name));
+        }
+        if let Some(letter) = find_duplicate(shapes.iter().map(|shape| &shape.opponent))
+            .or_else(|| find_duplicate(shapes.iter().map(|shape| &shape.player)))
+            .or_else(|| find_duplicate([&outcomes.loss, &outcomes.draw, &outcomes.win].into_iter()))
+        {
+            return Err(RuleSetError::DuplicateLetter(letter));
+        }
+        let mut wins = vec![vec![false; shapes.len()]; shapes.len()];
+        for (winner, shape) in shapes.iter().enumerate() {
+            for beaten in shape.beats.iter() {
+                let loser = shapes
+                    .iter()
+                    .position(|other| other.name == *beaten)
+                    .ok_or_else(|| RuleSetError::UnknownShape(beaten.clone()))?;
+                if winner == loser {
+                    return Err(RuleSetError::BeatsItself(shape.name.clone()));
+                }
+                wins[winner][loser] = true;
+            }
+        }
+        for first in 0..shapes.len() {
+            for second in first + 1..shapes.len() {
+                if wins[first][second] && wins[second][first] {
+                    return Err(RuleSetError::BeatEachOther(
+                        shapes[first].name.clone(),
+                        shapes[second].name.clone(),
+                    ));
+                }
+            }
+        }
+        let rules = RuleSet {
+            shapes,
+            outcomes,
+            wins,
+        };
+        // Part 2 needs a shape for every opponent and outcome the guide can ask for
+        for (index, opponent) in rules.shapes.iter().enumerate() {
+            for outcome in OUTCOMES {
+                if rules.for_outcome(Shape(index), outcome).is_none() {
+                    return Err(RuleSetError::NoShapeForOutcome(
+                        opponent.name.clone(),
+                        outcome,
+                    ));
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    fn rps() -> RuleSet {
+        RuleSet::new(
+            vec![
+                ShapeRule::new("Rock", 1, "A", "X", &["Scissors"]),
+                ShapeRule::new("Paper", 2, "B", "Y", &["Rock"]),
+                ShapeRule::new("Scissors", 3, "C", "Z", &["Paper"]),
+            ],
+            OutcomeLetters::default(),
+        )
+        .unwrap()
+    }
+
+    fn rpsls() -> RuleSet {
+        RuleSet::new(
+            vec![
+                ShapeRule::new("Rock", 1, "A", "X", &["Scissors", "Lizard"]),
+                ShapeRule::new("Paper", 2, "B", "Y", &["Rock", "Spock"]),
+                ShapeRule::new("Scissors", 3, "C", "Z", &["Paper", "Lizard"]),
+                ShapeRule::new("Lizard", 4, "D", "W", &["Spock", "Paper"]),
+                ShapeRule::new("Spock", 5, "E", "V", &["Scissors", "Rock"]),
+            ],
+            OutcomeLetters::default(),
+        )
+        .unwrap()
+    }
+
+    fn score(&self, shape: Shape) -> u32 {
+        self.shapes[shape.0].score
+    }
+
+    fn beats(&self, winner: Shape, loser: Shape) -> bool {
+        self.wins[winner.0][loser.0]
+    }
+
+    fn outcome(&self, player: Shape, opponent: Shape) -> Outcome {
+        if self.beats(player, opponent) {
+            Outcome::Win
+        } else if self.beats(opponent, player) {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    // Picks the first declared shape when several give the same outcome
+    fn for_outcome(&self, opponent: Shape, outcome: Outcome) -> Option<Shape> {
+        (0..self.shapes.len())
+            .map(Shape)
+            .find(|shape| outcome == self.outcome(*shape, opponent))
+    }
+
+    fn opponent_shape(&self, letter: &str) -> Option<Shape> {
+        self.shapes
+            .iter()
+            .position(|shape| shape.opponent == letter)
+            .map(Shape)
+    }
+
+    fn player_shape(&self, letter: &str) -> Option<Shape> {
+        self.shapes
+            .iter()
+            .position(|shape| shape.player == letter)
+            .map(Shape)
+    }
+
+    fn outcome_for_letter(&self, letter: &str) -> Option<Outcome> {
+        if self.outcomes.loss == letter {
+            Some(Outcome::Loss)
+        } else if self.outcomes.draw == letter {
+            Some(Outcome::Draw)
+        } else if self.outcomes.win == letter {
+            Some(Outcome::Win)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RoundError {
+    MissingColumn { line: usize },
+    UnknownLetter { line: usize, letter: String },
+}
+
+impl fmt::Display for RoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundError::MissingColumn { line } => {
+                write!(f, "Line {}: expected two columns", line)
+            }
+            RoundError::UnknownLetter { line, letter } => {
+                write!(f, "Line {}: unknown letter {:?}", line, letter)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Round {
+    opponent: Shape,
+    player: Shape,
+}
+
+impl Round {
+    fn score(&self, rules: &RuleSet) -> u32 {
+        rules.score(self.player) + rules.outcome(self.player, self.opponent) as u32
+    }
+}
+
+type Decoder = fn(&RuleSet, Shape, &str) -> Option<Shape>;
+
+fn decode_shape(rules: &RuleSet, _opponent: Shape, column: &str) -> Option<Shape> {
+    rules.player_shape(column)
+}
+
+fn decode_outcome(rules: &RuleSet, opponent: Shape, column: &str) -> Option<Shape> {
+    rules.for_outcome(opponent, rules.outcome_for_letter(column)?)
+}
+
+fn parse_round(
+    rules: &RuleSet,
+    line: usize,
+    content: &str,
+    decoder: Decoder,
+) -> Result<Round, RoundError> {
+    let mut columns = content.split_whitespace();
+    let (opponent, player) = match (columns.next(), columns.next()) {
+        (Some(opponent), Some(player)) => (opponent, player),
+        _ => return Err(RoundError::MissingColumn { line }),
     };
-    // A = Rock
-    // B = Paper
-    // C = Scissors
-    // X = Loss
-    // Y = Draw
-    // Z = Win
-    static ref CHOICE_MAP: HashMap<char, HashMap<char, u32>> = {
-        let mut map = HashMap::new();
-        map.insert('A', {
-            let mut inner_map = HashMap::new();
-            inner_map.insert('X', ResultScore::Loss as u32 + ChoiceScore::Scissors as u32);
-            inner_map.insert('Y', ResultScore::Draw as u32 + ChoiceScore::Rock as u32);
-            inner_map.insert('Z', ResultScore::Win as u32 + ChoiceScore::Paper as u32);
-            inner_map
-        });
-        map.insert('B', {
-            let mut inner_map = HashMap::new();
-            inner_map.insert('X', ResultScore::Loss as u32 + ChoiceScore::Rock as u32);
-            inner_map.insert('Y', ResultScore::Draw as u32 + ChoiceScore::Paper as u32);
-            inner_map.insert('Z', ResultScore::Win as u32 + ChoiceScore::Scissors as u32);
-            inner_map
-        });
-        map.insert('C', {
-            let mut inner_map = HashMap::new();
-            inner_map.insert('X', ResultScore::Loss as u32 + ChoiceScore::Paper as u32);
-            inner_map.insert('Y', ResultScore::Draw as u32 + ChoiceScore::Scissors as u32);
-            inner_map.insert('Z', ResultScore::Win as u32 + ChoiceScore::Rock as u32);
-            inner_map
-        });
-        map
+    let unknown = |letter: &str| RoundError::UnknownLetter {
+        line,
+        letter: letter.to_string(),
     };
+    let opponent_shape = rules
+        .opponent_shape(opponent)
+        .ok_or_else(|| unknown(opponent))?;
+    let player = decoder(rules, opponent_shape, player).ok_or_else(|| unknown(player))?;
+    Ok(Round {
+        opponent: opponent_shape,
+        player,
+    })
+}
+
+fn parse_rounds(rules: &RuleSet, input: &str, decoder: Decoder) -> Result<Vec<Round>, RoundError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| parse_round(rules, index + 1, line, decoder))
+        .collect()
+}
+
+fn total_score(rules: &RuleSet, input: &str, decoder: Decoder) -> Result<u32, RoundError> {
+    Ok(parse_rounds(rules, input, decoder)?
+        .iter()
+        .map(|round| round.score(rules))
+        .sum())
+}
+
+#[cfg(not(tarpaulin_include))]
+fn or_exit<T, E: fmt::Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        exit(1);
+    })
+}
+
+#[cfg(not(tarpaulin_include))]
+fn load_rules(args: &[String]) -> RuleSet {
+    let rules = args
+        .iter()
+        .position(|arg| "--rules" == arg)
+        .and_then(|index| args.get(index + 1));
+    match rules.map(String::as_str) {
+        None | Some("rps") => RuleSet::rps(),
+        Some("rpsls") => RuleSet::rpsls(),
+        Some(path) => or_exit(
+            read_to_string(path)
+                .expect("Unable to read rule set")
+                .parse::<RuleSet>(),
+        ),
+    }
 }
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
     let input = read_to_string("input.txt").expect("Unable to read input file");
-    println!("Part 1: {}", compute_score(&input));
-    println!("Part 2: {}", compute_choosing_score(&input));
-}
-
-fn compute_score(input: &str) -> u32 {
-    let mut score = 0;
-    for line in input.lines() {
-        let mut choices = line.trim().split(" ");
-        let player_one_choice = choices.next().unwrap().chars().next().unwrap();
-        let player_two_choice = choices.next().unwrap().chars().next().unwrap();
-        score += SCORING_MAP
-            .get(&player_one_choice)
-            .unwrap()
-            .get(&player_two_choice)
-            .unwrap();
+    let args = args().collect::<Vec<String>>();
+    let rules = load_rules(&args);
+    if args.iter().any(|arg| "--report" == arg) {
+        let strategies = [
+            (
+                "Part 1",
+                or_exit(round_reports(&rules, &input, decode_shape)),
+            ),
+            (
+                "Part 2",
+                or_exit(round_reports(&rules, &input, decode_outcome)),
+            ),
+        ];
+        if args.iter().any(|arg| "--csv" == arg) {
+            print!("{}", render_csv(&strategies));
+            println!();
+            print!("{}", render_tally_csv(&strategies));
+            return;
+        }
+        for (strategy, reports) in strategies.iter() {
+            println!("{}: {}", strategy, Tally::from_reports(reports));
+            print!("{}", render_table(reports));
+        }
+        return;
+    }
+    if args.iter().any(|arg| "--optimize" == arg) {
+        print_decodings("Shape decodings", &or_exit(shape_decodings(&rules, &input)));
+        print_decodings(
+            "Outcome decodings",
+            &or_exit(outcome_decodings(&rules, &input)),
+        );
+        return;
     }
-    score
+    println!("Part 1: {}", or_exit(compute_score(&rules, &input)));
+    println!(
+        "Part 2: {}",
+        or_exit(compute_choosing_score(&rules, &input))
+    );
 }
 
-fn compute_choosing_score(input: &str) -> u32 {
-    let mut score = 0;
-    for line in input.lines() {
-        let mut choices = line.trim().split(" ");
-        let player_one_choice = choices.next().unwrap().chars().next().unwrap();
-        let player_two_choice = choices.next().unwrap().chars().next().unwrap();
-        score += CHOICE_MAP
-            .get(&player_one_choice)
-            .unwrap()
-            .get(&player_two_choice)
-            .unwrap();
+#[cfg(not(tarpaulin_include))]
+fn print_decodings(title: &str, decodings: &[Decoding]) {
+    println!("{}:", title);
+    for decoding in decodings {
+        println!("  {}: {}", decoding.description, decoding.score);
+    }
+    if let Some((best, worst)) = best_and_worst(decodings) {
+        println!("  best {}: {}", best.description, best.score);
+        println!("  worst {}: {}", worst.description, worst.score);
+    }
+}
+
+fn compute_score(rules: &RuleSet, input: &str) -> Result<u32, RoundError> {
+    total_score(rules, input, decode_shape)
+}
+
+fn compute_choosing_score(rules: &RuleSet, input: &str) -> Result<u32, RoundError> {
+    total_score(rules, input, decode_outcome)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct RoundReport {
+    round: usize,
+    opponent: String,
+    player: String,
+    outcome: Outcome,
+    shape_points: u32,
+    outcome_points: u32,
+    total: u32,
+}
+
+fn round_reports(
+    rules: &RuleSet,
+    input: &str,
+    decoder: Decoder,
+) -> Result<Vec<RoundReport>, RoundError> {
+    let mut total = 0;
+    Ok(parse_rounds(rules, input, decoder)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, round)| {
+            let outcome = rules.outcome(round.player, round.opponent);
+            total += round.score(rules);
+            RoundReport {
+                round: index + 1,
+                opponent: rules.shapes[round.opponent.0].name.clone(),
+                player: rules.shapes[round.player.0].name.clone(),
+                outcome,
+                shape_points: rules.score(round.player),
+                outcome_points: outcome as u32,
+                total,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
+struct Tally {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+}
+
+impl Tally {
+    fn from_reports(reports: &[RoundReport]) -> Tally {
+        let mut tally = Tally::default();
+        for report in reports {
+            match report.outcome {
+                Outcome::Win => tally.wins += 1,
+                Outcome::Draw => tally.draws += 1,
+                Outcome::Loss => tally.losses += 1,
+            }
+        }
+        tally
+    }
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} wins, {} draws, {} losses",
+            self.wins, self.draws, self.losses
+        )
+    }
+}
+
+fn render_table(reports: &[RoundReport]) -> String {
+    let rows = reports
+        .iter()
+        .map(|report| {
+            vec![
+                report.round.to_string(),
+                report.opponent.clone(),
+                report.player.clone(),
+                format!("{:?}", report.outcome),
+                report.shape_points.to_string(),
+                report.outcome_points.to_string(),
+                report.total.to_string(),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    table::render_table(
+        &[
+            "Round",
+            "Opponent",
+            "Player",
+            "Outcome",
+            "Shape pts",
+            "Outcome pts",
+            "Total",
+        ],
+        &rows,
+    )
+}
+
+fn render_csv(strategies: &[(&str, Vec<RoundReport>)]) -> String {
+    let rows = strategies
+        .iter()
+        .flat_map(|(strategy, reports)| {
+            reports.iter().map(move |report| {
+                vec![
+                    strategy.to_string(),
+                    report.round.to_string(),
+                    report.opponent.clone(),
+                    report.player.clone(),
+                    format!("{:?}", report.outcome),
+                    report.shape_points.to_string(),
+                    report.outcome_points.to_string(),
+                    report.total.to_string(),
+                ]
+            })
+        })
+        .collect::<Vec<Vec<String>>>();
+    table::render_csv(
+        &[
+            "strategy",
+            "round",
+            "opponent",
+            "player",
+            "outcome",
+            "shape_points",
+            "outcome_points",
+            "total",
+        ],
+        &rows,
+    )
+}
+
+// A second table, so the per-round CSV keeps one kind of row
+fn render_tally_csv(strategies: &[(&str, Vec<RoundReport>)]) -> String {
+    let rows = strategies
+        .iter()
+        .map(|(strategy, reports)| {
+            let tally = Tally::from_reports(reports);
+            vec![
+                strategy.to_string(),
+                tally.wins.to_string(),
+                tally.draws.to_string(),
+                tally.losses.to_string(),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    table::render_csv(&["strategy", "wins", "draws", "losses"], &rows)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Decoding {
+    description: String,
+    score: u32,
+}
+
+fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    let mut permutations = Vec::new();
+    for index in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(index);
+        for mut permutation in self::permutations(&rest) {
+            permutation.insert(0, first.clone());
+            permutations.push(permutation);
+        }
     }
-    score
+    permutations
+}
+
+// Scores every way of assigning the player letters to the rule set's shapes
+fn shape_decodings(rules: &RuleSet, input: &str) -> Result<Vec<Decoding>, RoundError> {
+    let letters = rules
+        .shapes
+        .iter()
+        .map(|shape| shape.player.clone())
+        .collect::<Vec<String>>();
+    permutations(&(0..rules.shapes.len()).collect::<Vec<usize>>())
+        .into_iter()
+        .map(|order| {
+            let mut decoded = rules.clone();
+            for (letter, index) in letters.iter().zip(order.iter()) {
+                decoded.shapes[*index].player = letter.clone();
+            }
+            Ok(Decoding {
+                description: letters
+                    .iter()
+                    .zip(order.iter())
+                    .map(|(letter, index)| format!("{}={}", letter, rules.shapes[*index].name))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                score: compute_score(&decoded, input)?,
+            })
+        })
+        .collect()
+}
+
+// Scores every way of assigning the outcome letters to loss, draw and win
+fn outcome_decodings(rules: &RuleSet, input: &str) -> Result<Vec<Decoding>, RoundError> {
+    let letters = [
+        rules.outcomes.loss.clone(),
+        rules.outcomes.draw.clone(),
+        rules.outcomes.win.clone(),
+    ];
+    permutations(&[Outcome::Loss, Outcome::Draw, Outcome::Win])
+        .into_iter()
+        .map(|order| {
+            let mut decoded = rules.clone();
+            for (letter, outcome) in letters.iter().zip(order.iter()) {
+                match outcome {
+                    Outcome::Loss => decoded.outcomes.loss = letter.clone(),
+                    Outcome::Draw => decoded.outcomes.draw = letter.clone(),
+                    Outcome::Win => decoded.outcomes.win = letter.clone(),
+                }
+            }
+            Ok(Decoding {
+                description: letters
+                    .iter()
+                    .zip(order.iter())
+                    .map(|(letter, outcome)| format!("{}={:?}", letter, outcome))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                score: compute_choosing_score(&decoded, input)?,
+            })
+        })
+        .collect()
+}
+
+// Ties go to the earliest decoding, so the guide's own reading wins them. max_by_key
+// keeps the last maximum, hence the rev(), while min_by_key keeps the first minimum
+fn best_and_worst(decodings: &[Decoding]) -> Option<(&Decoding, &Decoding)> {
+    let best = decodings
+        .iter()
+        .rev()
+        .max_by_key(|decoding| decoding.score)?;
+    let worst = decodings.iter().min_by_key(|decoding| decoding.score)?;
+    Some((best, worst))
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -132,11 +691,22 @@ fn compute_choosing_score(input: &str) -> u32 {
 mod tests {
     use super::*;
 
+    fn shape(rules: &RuleSet, name: &str) -> Shape {
+        Shape(
+            rules
+                .shapes
+                .iter()
+                .position(|shape| shape.name == name)
+                .unwrap(),
+        )
+    }
+
     #[test]
     fn test_compute_score() {
         assert_eq!(
-            15,
+            Ok(15),
             compute_score(
+                &RuleSet::rps(),
                 "A Y
                 B X
                 C Z"
@@ -147,12 +717,391 @@ mod tests {
     #[test]
     fn test_compute_choosing_score() {
         assert_eq!(
-            12,
+            Ok(12),
             compute_choosing_score(
+                &RuleSet::rps(),
                 "A Y
                 B X
                 C Z"
             )
         );
     }
+
+    #[test]
+    fn test_round_reports() {
+        let rules = RuleSet::rps();
+        let reports = round_reports(&rules, "A Y\nB X\n\nC Z", decode_shape).unwrap();
+        assert_eq!(
+            vec![
+                RoundReport {
+                    round: 1,
+                    opponent: "Rock".to_string(),
+                    player: "Paper".to_string(),
+                    outcome: Outcome::Win,
+                    shape_points: 2,
+                    outcome_points: 6,
+                    total: 8,
+                },
+                RoundReport {
+                    round: 2,
+                    opponent: "Paper".to_string(),
+                    player: "Rock".to_string(),
+                    outcome: Outcome::Loss,
+                    shape_points: 1,
+                    outcome_points: 0,
+                    total: 9,
+                },
+                RoundReport {
+                    round: 3,
+                    opponent: "Scissors".to_string(),
+                    player: "Scissors".to_string(),
+                    outcome: Outcome::Draw,
+                    shape_points: 3,
+                    outcome_points: 3,
+                    total: 15,
+                },
+            ],
+            reports
+        );
+        assert_eq!(
+            Tally {
+                wins: 1,
+                draws: 1,
+                losses: 1,
+            },
+            Tally::from_reports(&reports)
+        );
+        let reports = round_reports(&rules, "A Y\nB X\nC Z", decode_outcome).unwrap();
+        assert_eq!(12, reports[2].total);
+        assert_eq!(
+            "1 wins, 1 draws, 1 losses",
+            Tally::from_reports(&reports).to_string()
+        );
+    }
+
+    #[test]
+    fn test_render_round_reports() {
+        let reports = round_reports(&RuleSet::rps(), "A Y\nC Z", decode_shape).unwrap();
+        assert_eq!(
+            "Round  Opponent  Player    Outcome  Shape pts  Outcome pts  Total
+1      Rock      Paper     Win      2          6            8
+2      Scissors  Scissors  Draw     3          3            14
+",
+            render_table(&reports)
+        );
+        assert_eq!(
+            "strategy,round,opponent,player,outcome,shape_points,outcome_points,total
+Part 1,1,Rock,Paper,Win,2,6,8
+Part 1,2,Scissors,Scissors,Draw,3,3,14
+",
+            render_csv(&[("Part 1", reports.clone())])
+        );
+        let part_2 = round_reports(&RuleSet::rps(), "A Y\nC Z", decode_outcome).unwrap();
+        assert_eq!(
+            "strategy,wins,draws,losses
+Part 1,1,1,0
+Part 2,1,1,0
+",
+            render_tally_csv(&[("Part 1", reports), ("Part 2", part_2)])
+        );
+    }
+
+    #[test]
+    fn test_permutations() {
+        assert_eq!(vec![Vec::<u8>::new()], permutations::<u8>(&[]));
+        assert_eq!(
+            vec![
+                vec![1, 2, 3],
+                vec![1, 3, 2],
+                vec![2, 1, 3],
+                vec![2, 3, 1],
+                vec![3, 1, 2],
+                vec![3, 2, 1],
+            ],
+            permutations(&[1, 2, 3])
+        );
+        assert_eq!(120, permutations(&[1, 2, 3, 4, 5]).len());
+    }
+
+    #[test]
+    fn test_shape_decodings() {
+        let decodings = shape_decodings(&RuleSet::rps(), "A Y\nB X\nC Z").unwrap();
+        assert_eq!(6, decodings.len());
+        assert_eq!(
+            Decoding {
+                description: "X=Rock Y=Paper Z=Scissors".to_string(),
+                score: 15,
+            },
+            decodings[0]
+        );
+        // X=Paper Y=Rock Z=Scissors: A Y draws, B X draws, C Z draws
+        assert_eq!(
+            Decoding {
+                description: "X=Paper Y=Rock Z=Scissors".to_string(),
+                score: 1 + 3 + 2 + 3 + 3 + 3,
+            },
+            decodings[2]
+        );
+        let (best, worst) = best_and_worst(&decodings).unwrap();
+        // Every round is a win when each letter beats the opponent beside it
+        assert_eq!("X=Scissors Y=Paper Z=Rock", best.description);
+        assert_eq!(8 + 9 + 7, best.score);
+        assert_eq!("X=Rock Y=Scissors Z=Paper", worst.description);
+        assert_eq!(3 + 1 + 2, worst.score);
+    }
+
+    #[test]
+    fn test_outcome_decodings() {
+        let decodings = outcome_decodings(&RuleSet::rps(), "A Y\nB X\nC Z").unwrap();
+        assert_eq!(6, decodings.len());
+        assert_eq!(
+            Decoding {
+                description: "X=Loss Y=Draw Z=Win".to_string(),
+                score: 12,
+            },
+            decodings[0]
+        );
+        // X=Win Y=Loss Z=Draw: lose to Rock, beat Paper, draw with Scissors
+        assert_eq!(
+            Decoding {
+                description: "X=Win Y=Loss Z=Draw".to_string(),
+                score: 3 + 9 + 6,
+            },
+            decodings[4]
+        );
+        let (best, worst) = best_and_worst(&decodings).unwrap();
+        assert_eq!(&decodings[4], best);
+        assert_eq!(&decodings[0], worst);
+        assert!(best_and_worst(&[]).is_none());
+    }
+
+    #[test]
+    fn test_best_and_worst_ties() {
+        let decoding = |description: &str, score| Decoding {
+            description: description.to_string(),
+            score,
+        };
+        let decodings = [
+            decoding("first", 5),
+            decoding("second", 9),
+            decoding("third", 5),
+            decoding("fourth", 9),
+        ];
+        let (best, worst) = best_and_worst(&decodings).unwrap();
+        assert_eq!("second", best.description);
+        assert_eq!("first", worst.description);
+    }
+
+    #[test]
+    fn test_rule_set_letters() {
+        let rules = RuleSet::rps();
+        assert_eq!(Some(shape(&rules, "Rock")), rules.opponent_shape("A"));
+        assert_eq!(Some(shape(&rules, "Paper")), rules.player_shape("Y"));
+        assert_eq!(None, rules.opponent_shape("D"));
+        assert_eq!(None, rules.player_shape("A"));
+        assert_eq!(Some(Outcome::Loss), rules.outcome_for_letter("X"));
+        assert_eq!(Some(Outcome::Draw), rules.outcome_for_letter("Y"));
+        assert_eq!(Some(Outcome::Win), rules.outcome_for_letter("Z"));
+        assert_eq!(None, rules.outcome_for_letter("A"));
+    }
+
+    #[test]
+    fn test_rule_set_outcome() {
+        let rules = RuleSet::rps();
+        let shape = |name| shape(&rules, name);
+        assert_eq!(
+            Outcome::Win,
+            rules.outcome(shape("Rock"), shape("Scissors"))
+        );
+        assert_eq!(Outcome::Loss, rules.outcome(shape("Rock"), shape("Paper")));
+        assert_eq!(Outcome::Draw, rules.outcome(shape("Paper"), shape("Paper")));
+        assert_eq!(
+            Outcome::Win,
+            rules.outcome(shape("Scissors"), shape("Paper"))
+        );
+    }
+
+    #[test]
+    fn test_rule_set_for_outcome() {
+        for rules in [RuleSet::rps(), RuleSet::rpsls()] {
+            for opponent in (0..rules.shapes.len()).map(Shape) {
+                for outcome in OUTCOMES {
+                    let shape = rules.for_outcome(opponent, outcome).unwrap();
+                    assert_eq!(outcome, rules.outcome(shape, opponent));
+                }
+            }
+        }
+        let rules = RuleSet::rps();
+        assert_eq!(
+            Some(shape(&rules, "Paper")),
+            rules.for_outcome(shape(&rules, "Rock"), Outcome::Win)
+        );
+    }
+
+    #[test]
+    fn test_rpsls() {
+        let rules = RuleSet::rpsls();
+        let shape = |name| shape(&rules, name);
+        assert_eq!(Outcome::Win, rules.outcome(shape("Spock"), shape("Rock")));
+        assert_eq!(
+            Outcome::Loss,
+            rules.outcome(shape("Spock"), shape("Lizard"))
+        );
+        assert_eq!(Outcome::Win, rules.outcome(shape("Lizard"), shape("Paper")));
+        for winner in 0..5 {
+            assert_eq!(2, rules.wins[winner].iter().filter(|win| **win).count());
+        }
+        // D W: Lizard draws with Lizard, E X: Rock loses to Spock
+        assert_eq!(Ok(4 + 3 + 1), compute_score(&rules, "D W\nE X"));
+        // D Z: beat Lizard with Rock, E X: lose to Spock with Rock
+        assert_eq!(Ok(1 + 6 + 1), compute_choosing_score(&rules, "D Z\nE X"));
+    }
+
+    #[test]
+    fn test_rule_set_from_str() {
+        let rules = include_str!("../rules/rpsls.toml")
+            .parse::<RuleSet>()
+            .unwrap();
+        assert_eq!(RuleSet::rpsls(), rules);
+        let rules = "[[shapes]]
+            name = \"Low\"
+            score = 1
+            opponent = \"L\"
+            player = \"1\"
+            beats = [\"High\"]
+
+            [[shapes]]
+            name = \"Mid\"
+            score = 2
+            opponent = \"M\"
+            player = \"2\"
+            beats = [\"Low\"]
+
+            [[shapes]]
+            name = \"High\"
+            score = 3
+            opponent = \"H\"
+            player = \"3\"
+            beats = [\"Mid\"]"
+            .parse::<RuleSet>()
+            .unwrap();
+        assert_eq!(OutcomeLetters::default(), rules.outcomes);
+        // M 1: Low loses to Mid, M 3: High beats Mid
+        assert_eq!(Ok(1 + 3 + 6), compute_score(&rules, "M 1\nM 3"));
+    }
+
+    #[test]
+    fn test_rule_set_errors() {
+        let shapes = |beats: &[&str]| {
+            vec![
+                ShapeRule::new("Heads", 1, "A", "X", beats),
+                ShapeRule::new("Tails", 2, "B", "Y", &["Heads"]),
+            ]
+        };
+        let outcomes = OutcomeLetters::default;
+        assert_eq!(
+            Err(RuleSetError::NoShapes),
+            RuleSet::new(vec![], outcomes())
+        );
+        assert_eq!(
+            Err(RuleSetError::UnknownShape("Edge".to_string())),
+            RuleSet::new(shapes(&["Edge"]), outcomes())
+        );
+        assert_eq!(
+            Err(RuleSetError::BeatsItself("Heads".to_string())),
+            RuleSet::new(shapes(&["Heads"]), outcomes())
+        );
+        assert_eq!(
+            Err(RuleSetError::BeatEachOther(
+                "Heads".to_string(),
+                "Tails".to_string()
+            )),
+            RuleSet::new(shapes(&["Tails"]), outcomes())
+        );
+        let mut duplicate = shapes(&[]);
+        duplicate[1].player = "X".to_string();
+        assert_eq!(
+            Err(RuleSetError::DuplicateLetter("X".to_string())),
+            RuleSet::new(duplicate, outcomes())
+        );
+        let mut duplicate = shapes(&[]);
+        duplicate[1].name = "Heads".to_string();
+        assert_eq!(
+            Err(RuleSetError::DuplicateName("Heads".to_string())),
+            RuleSet::new(duplicate, outcomes())
+        );
+        // Heads beats nothing, so no shape can lose against it
+        assert_eq!(
+            Err(RuleSetError::NoShapeForOutcome(
+                "Heads".to_string(),
+                Outcome::Loss
+            )),
+            RuleSet::new(shapes(&[]), outcomes())
+        );
+        assert_eq!(
+            "No shape gives a Loss against Heads",
+            RuleSetError::NoShapeForOutcome("Heads".to_string(), Outcome::Loss).to_string()
+        );
+        assert!(matches!(
+            "shapes = 1".parse::<RuleSet>(),
+            Err(RuleSetError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_round() {
+        let rules = RuleSet::rps();
+        let shape = |name| shape(&rules, name);
+        assert_eq!(
+            Ok(Round {
+                opponent: shape("Rock"),
+                player: shape("Paper"),
+            }),
+            parse_round(&rules, 1, "A Y", decode_shape)
+        );
+        assert_eq!(
+            Ok(Round {
+                opponent: shape("Rock"),
+                player: shape("Rock"),
+            }),
+            parse_round(&rules, 1, "A Y", decode_outcome)
+        );
+        assert_eq!(
+            Err(RoundError::MissingColumn { line: 2 }),
+            parse_round(&rules, 2, "A", decode_shape)
+        );
+        assert_eq!(
+            Err(RoundError::UnknownLetter {
+                line: 3,
+                letter: "Q".to_string(),
+            }),
+            parse_round(&rules, 3, "A Q", decode_outcome)
+        );
+        let error = parse_rounds(&rules, "A X\n\nD Y", decode_shape).unwrap_err();
+        assert_eq!(
+            RoundError::UnknownLetter {
+                line: 3,
+                letter: "D".to_string(),
+            },
+            error
+        );
+        assert_eq!("Line 3: unknown letter \"D\"", error.to_string());
+        assert_eq!(
+            Err(RoundError::UnknownLetter {
+                line: 2,
+                letter: "Q".to_string(),
+            }),
+            compute_choosing_score(&rules, "A Y\nA Q")
+        );
+    }
+
+    #[test]
+    fn test_round_score() {
+        let rules = RuleSet::rps();
+        let round = Round {
+            opponent: shape(&rules, "Scissors"),
+            player: shape(&rules, "Rock"),
+        };
+        assert_eq!(7, round.score(&rules));
+    }
 }
@@ -12,29 +12,316 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use aoc::variants::Variants;
 use std::collections::HashSet;
+use std::env::args;
+use std::fmt;
 use std::fs::read_to_string;
+use std::process::exit;
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
     let input = read_to_string("input.txt").expect("Unable to read input file");
-    println!("Part 1: {}", sum_shared_priority(&input));
-    println!("Part 2: {}", sum_grouped_priority(&input));
+    if args().any(|arg| "--items" == arg) {
+        print_items(&input);
+        return;
+    }
+    Variants::new()
+        .register(1, "bitmask", |input| {
+            or_exit(sum_shared_priority(input)).to_string()
+        })
+        .register(1, "hashset", |input| {
+            sum_shared_priority_hashset(input).to_string()
+        })
+        .register(2, "bitmask", |input| {
+            or_exit(sum_grouped_priority(input, requested_group_size(args()))).to_string()
+        })
+        .register(2, "hashset", |input| {
+            sum_grouped_priority_hashset(input, requested_group_size(args())).to_string()
+        })
+        .run_from_args(&input);
+}
+
+#[cfg(not(tarpaulin_include))]
+fn or_exit<T>(result: Result<T, RucksackError>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        exit(1);
+    })
+}
+
+#[cfg(not(tarpaulin_include))]
+fn print_items(input: &str) {
+    let shared = or_exit(shared_items(input));
+    for (line, item) in shared {
+        println!("Line {}: {} ({})", line, item, item.priority());
+    }
+    let badges = or_exit(group_badges(input, requested_group_size(args())));
+    for (index, badge) in badges.iter().enumerate() {
+        println!("Group {}: {} ({})", index + 1, badge, badge.priority());
+    }
+}
+
+fn requested_group_size(args: impl IntoIterator<Item = String>) -> usize {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if "--group-size" == arg {
+            return args
+                .next()
+                .and_then(|size| size.parse::<usize>().ok())
+                .expect("--group-size needs a number");
+        }
+    }
+    3
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RucksackError {
+    InvalidItem {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    OddLength {
+        line: usize,
+        length: usize,
+    },
+    NoSharedItem {
+        line: usize,
+    },
+    SeveralSharedItems {
+        line: usize,
+        items: Vec<Item>,
+    },
+    EmptyGroups,
+    RaggedGroup {
+        group: usize,
+        size: usize,
+        expected: usize,
+    },
+    NoCommonItem {
+        group: usize,
+    },
+    SeveralCommonItems {
+        group: usize,
+        items: Vec<Item>,
+    },
+}
+
+impl fmt::Display for RucksackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RucksackError::InvalidItem {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "Line {}, column {}: {:?} is not an item",
+                line, column, character
+            ),
+            RucksackError::OddLength { line, length } => write!(
+                f,
+                "Line {}: {} items cannot be split into two compartments",
+                line, length
+            ),
+            RucksackError::NoSharedItem { line } => {
+                write!(f, "Line {}: compartments share no item", line)
+            }
+            RucksackError::SeveralSharedItems { line, items } => write!(
+                f,
+                "Line {}: compartments share {}",
+                line,
+                format_items(items)
+            ),
+            RucksackError::EmptyGroups => write!(f, "Group size must be at least 1"),
+            RucksackError::RaggedGroup {
+                group,
+                size,
+                expected,
+            } => write!(
+                f,
+                "Group {} has {} rucksacks instead of {}",
+                group, size, expected
+            ),
+            RucksackError::NoCommonItem { group } => {
+                write!(f, "Group {} has no item in common", group)
+            }
+            RucksackError::SeveralCommonItems { group, items } => {
+                write!(f, "Group {} has {} in common", group, format_items(items))
+            }
+        }
+    }
+}
+
+fn format_items(items: &[Item]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Item(u8);
+
+impl Item {
+    fn new(byte: u8) -> Option<Item> {
+        byte.is_ascii_alphabetic().then_some(Item(byte))
+    }
+
+    fn from_priority(priority: u32) -> Item {
+        match priority {
+            1..=26 => Item(b'a' + priority as u8 - 1),
+            27..=52 => Item(b'A' + priority as u8 - 27),
+            _ => panic!("No item has priority {}", priority),
+        }
+    }
+
+    fn priority(self) -> u32 {
+        match self.0 {
+            b'a'..=b'z' => (self.0 - b'a' + 1) as u32,
+            _ => (self.0 - b'A' + 27) as u32,
+        }
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0 as char)
+    }
+}
+
+// Bits 1 through 52 hold the items, keyed by priority
+type ItemSet = u64;
+
+fn set_items(mut set: ItemSet) -> Vec<Item> {
+    let mut items = Vec::new();
+    while 0 != set {
+        items.push(Item::from_priority(set.trailing_zeros()));
+        set &= set - 1;
+    }
+    items
+}
+
+// Only called on the first byte that is not a letter, so index is a char boundary
+fn invalid_item(line: usize, rucksack: &str, index: usize) -> RucksackError {
+    RucksackError::InvalidItem {
+        line,
+        column: rucksack[..index].chars().count() + 1,
+        character: rucksack[index..].chars().next().unwrap(),
+    }
+}
+
+fn item_set(line: usize, rucksack: &str) -> Result<ItemSet, RucksackError> {
+    let mut set = 0;
+    for (index, &byte) in rucksack.as_bytes().iter().enumerate() {
+        let item = Item::new(byte).ok_or_else(|| invalid_item(line, rucksack, index))?;
+        set |= 1 << item.priority();
+    }
+    Ok(set)
+}
+
+fn shared_items(input: &str) -> Result<Vec<(usize, Item)>, RucksackError> {
+    let mut shared_items = Vec::new();
+    for (index, rucksack) in input.lines().enumerate() {
+        let line = index + 1;
+        let rucksack = rucksack.trim();
+        if rucksack.is_empty() {
+            continue;
+        }
+        let half = rucksack.len() / 2;
+        let (mut first, mut second) = (0, 0);
+        for (index, &byte) in rucksack.as_bytes().iter().enumerate() {
+            let item = Item::new(byte).ok_or_else(|| invalid_item(line, rucksack, index))?;
+            if index < half {
+                first |= 1 << item.priority();
+            } else {
+                second |= 1 << item.priority();
+            }
+        }
+        if 1 == rucksack.len() % 2 {
+            return Err(RucksackError::OddLength {
+                line,
+                length: rucksack.len(),
+            });
+        }
+        match set_items(first & second).as_slice() {
+            [] => return Err(RucksackError::NoSharedItem { line }),
+            [item] => shared_items.push((line, *item)),
+            items => {
+                return Err(RucksackError::SeveralSharedItems {
+                    line,
+                    items: items.to_vec(),
+                })
+            }
+        }
+    }
+    Ok(shared_items)
+}
+
+fn group_badges(input: &str, group_size: usize) -> Result<Vec<Item>, RucksackError> {
+    if 0 == group_size {
+        return Err(RucksackError::EmptyGroups);
+    }
+    let rucksacks = input
+        .lines()
+        .enumerate()
+        .map(|(index, rucksack)| (index + 1, rucksack.trim()))
+        .filter(|(_, rucksack)| !rucksack.is_empty())
+        .map(|(line, rucksack)| item_set(line, rucksack))
+        .collect::<Result<Vec<ItemSet>, RucksackError>>()?;
+    let mut badges = Vec::new();
+    for (index, group) in rucksacks.chunks(group_size).enumerate() {
+        if group_size != group.len() {
+            return Err(RucksackError::RaggedGroup {
+                group: index + 1,
+                size: group.len(),
+                expected: group_size,
+            });
+        }
+        let shared = group.iter().fold(!0, |shared, set| shared & set);
+        match set_items(shared).as_slice() {
+            [] => return Err(RucksackError::NoCommonItem { group: index + 1 }),
+            [item] => badges.push(*item),
+            items => {
+                return Err(RucksackError::SeveralCommonItems {
+                    group: index + 1,
+                    items: items.to_vec(),
+                })
+            }
+        }
+    }
+    Ok(badges)
+}
+
+fn sum_shared_priority(input: &str) -> Result<u32, RucksackError> {
+    Ok(shared_items(input)?
+        .iter()
+        .map(|(_, item)| item.priority())
+        .sum())
+}
+
+fn sum_grouped_priority(input: &str, group_size: usize) -> Result<u32, RucksackError> {
+    Ok(group_badges(input, group_size)?
+        .iter()
+        .map(|item| item.priority())
+        .sum())
 }
 
 fn compute_priority(available: Vec<char>) -> u32 {
     let mut priority = 0;
     for character in available {
         if character.is_uppercase() {
-            priority += (character as u8 - 'A' as u8 + 27) as u32;
+            priority += (character as u8 - b'A' + 27) as u32;
         } else {
-            priority += (character as u8 - 'a' as u8 + 1) as u32;
+            priority += (character as u8 - b'a' + 1) as u32;
         }
     }
     priority
 }
 
-fn sum_shared_priority(input: &str) -> u32 {
+fn sum_shared_priority_hashset(input: &str) -> u32 {
     let mut sum = 0;
     for line in input.lines() {
         if line.is_empty() {
@@ -47,35 +334,27 @@ fn sum_shared_priority(input: &str) -> u32 {
             first.insert(line.chars().nth(index).unwrap());
             second.insert(line.chars().nth(line.len() - index - 1).unwrap());
         }
-        let mut raw_intersection: Vec<&char> = first.intersection(&second).collect();
-        let intersection: Vec<char> = raw_intersection.drain(..).map(|&x| x).collect();
+        let intersection: Vec<char> = first.intersection(&second).copied().collect();
         sum += compute_priority(intersection);
     }
     sum
 }
 
-fn sum_grouped_priority(input: &str) -> u32 {
+// Intersects one set per rucksack, so any group size works; a short last group
+// just intersects fewer sets
+fn sum_grouped_priority_hashset(input: &str, group_size: usize) -> u32 {
+    if 0 == group_size {
+        return 0;
+    }
     let mut sum = 0;
     let lines = input.trim().lines().collect::<Vec<&str>>();
-    for index in (0..lines.len()).step_by(3) {
-        let mut first: HashSet<char> = HashSet::new();
-        let mut second: HashSet<char> = HashSet::new();
-        let mut third: HashSet<char> = HashSet::new();
-        for character in lines[index].trim().chars() {
-            first.insert(character);
-        }
-        for character in lines[index + 1].trim().chars() {
-            second.insert(character);
-        }
-        for character in lines[index + 2].trim().chars() {
-            third.insert(character);
+    for group in lines.chunks(group_size) {
+        let mut intersection: HashSet<char> = group[0].trim().chars().collect();
+        for line in group[1..].iter() {
+            let set: HashSet<char> = line.trim().chars().collect();
+            intersection = intersection.intersection(&set).copied().collect();
         }
-        let first_intersection: HashSet<char> = first.intersection(&second).map(|&x| x).collect();
-        let intersection: Vec<char> = first_intersection
-            .intersection(&third)
-            .map(|&x| x)
-            .collect();
-        sum += compute_priority(intersection);
+        sum += compute_priority(intersection.into_iter().collect());
     }
     sum
 }
@@ -93,10 +372,137 @@ mod tests {
         assert_eq!(55, compute_priority(vec!['A', 'B']));
     }
 
+    #[test]
+    fn test_item() {
+        assert_eq!(Some(Item(b'a')), Item::new(b'a'));
+        assert_eq!(None, Item::new(b'1'));
+        assert_eq!(None, Item::new(0xc3));
+        assert_eq!(1, Item(b'a').priority());
+        assert_eq!(26, Item(b'z').priority());
+        assert_eq!(27, Item(b'A').priority());
+        assert_eq!(52, Item(b'Z').priority());
+        for priority in 1..=52 {
+            assert_eq!(priority, Item::from_priority(priority).priority());
+        }
+        assert_eq!("p", Item(b'p').to_string());
+    }
+
+    #[test]
+    fn test_item_set() {
+        assert_eq!(Ok(0), item_set(1, ""));
+        assert_eq!(Ok(0b110), item_set(1, "abba"));
+        assert_eq!(Ok(1 << 52 | 1 << 27), item_set(1, "ZA"));
+        assert_eq!(
+            vec![Item(b'a'), Item(b'b'), Item(b'A'), Item(b'Z')],
+            set_items(item_set(1, "ZbAa").unwrap())
+        );
+        assert_eq!(
+            Err(RucksackError::InvalidItem {
+                line: 4,
+                column: 3,
+                character: '!',
+            }),
+            item_set(4, "ab!?")
+        );
+    }
+
+    #[test]
+    fn test_shared_items() {
+        assert_eq!(
+            Ok(vec![(1, Item(b'p')), (3, Item(b'L'))]),
+            shared_items(
+                "vJrwpWtwJgWrhcsFMMfFFhFp
+
+                jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"
+            )
+        );
+        assert_eq!(
+            Ok(vec![Item(b'r'), Item(b'Z')]),
+            group_badges(
+                "vJrwpWtwJgWrhcsFMMfFFhFp
+                jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+                PmmdzqPrVvPwwTWBwg
+                wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+                ttgJtRGJQctTZtZT
+                CrZsJsPPZsGzwwsLwLmpwMDw",
+                3
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_items() {
+        assert_eq!(
+            Err(RucksackError::InvalidItem {
+                line: 2,
+                column: 3,
+                character: 'é',
+            }),
+            sum_shared_priority("abca\n  aXéXa")
+        );
+        assert_eq!(
+            Err(RucksackError::InvalidItem {
+                line: 1,
+                column: 4,
+                character: '4',
+            }),
+            sum_shared_priority("abc4")
+        );
+        assert_eq!(
+            Err(RucksackError::InvalidItem {
+                line: 3,
+                column: 1,
+                character: '-',
+            }),
+            sum_grouped_priority("ab\nac\n-b\nbe", 2)
+        );
+        assert_eq!(
+            "Line 2, column 3: 'é' is not an item",
+            RucksackError::InvalidItem {
+                line: 2,
+                column: 3,
+                character: 'é',
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_variants_agree() {
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp
+            jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+            PmmdzqPrVvPwwTWBwg
+            wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+            ttgJtRGJQctTZtZT
+            CrZsJsPPZsGzwwsLwLmpwMDw";
+        assert_eq!(
+            Ok(sum_shared_priority_hashset(input)),
+            sum_shared_priority(input)
+        );
+        assert_eq!(
+            Ok(sum_grouped_priority_hashset(input, 3)),
+            sum_grouped_priority(input, 3)
+        );
+        for (group_input, group_size) in [
+            ("a\nb\nc", 1),
+            ("ab\nac\nbd\nbe", 2),
+            ("abc\ncad\nfea\nag", 4),
+        ] {
+            assert_eq!(
+                Ok(sum_grouped_priority_hashset(group_input, group_size)),
+                sum_grouped_priority(group_input, group_size)
+            );
+        }
+        assert_eq!(
+            Ok(sum_shared_priority_hashset("abcXdeYc\nAbZyzA")),
+            sum_shared_priority("abcXdeYc\nAbZyzA")
+        );
+    }
+
     #[test]
     fn test_sum_shared_priority() {
         assert_eq!(
-            157,
+            Ok(157),
             sum_shared_priority(
                 "vJrwpWtwJgWrhcsFMMfFFhFp
                 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
@@ -113,7 +519,7 @@ mod tests {
     #[test]
     fn test_sum_grouped_priority() {
         assert_eq!(
-            70,
+            Ok(70),
             sum_grouped_priority(
                 "vJrwpWtwJgWrhcsFMMfFFhFp
                 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
@@ -122,8 +528,73 @@ mod tests {
                 ttgJtRGJQctTZtZT
                 CrZsJsPPZsGzwwsLwLmpwMDw
 
-                "
+                ",
+                3
             )
         );
     }
+
+    #[test]
+    fn test_sum_grouped_priority_sizes() {
+        assert_eq!(Ok(1 + 2), sum_grouped_priority("ab\nac\nbd\nbe", 2));
+        assert_eq!(Ok(1), sum_grouped_priority("abc\ncad\nfea\nag", 4));
+        assert_eq!(Ok(1 + 2 + 3), sum_grouped_priority("a\nb\nc", 1));
+    }
+
+    #[test]
+    fn test_rucksack_errors() {
+        assert_eq!(
+            Err(RucksackError::OddLength { line: 2, length: 5 }),
+            sum_shared_priority("abca\nabcda")
+        );
+        assert_eq!(
+            Err(RucksackError::EmptyGroups),
+            sum_grouped_priority("a", 0)
+        );
+        assert_eq!(
+            Err(RucksackError::RaggedGroup {
+                group: 2,
+                size: 1,
+                expected: 2,
+            }),
+            sum_grouped_priority("ab\nac\nbd", 2)
+        );
+        assert_eq!(
+            Err(RucksackError::NoCommonItem { group: 1 }),
+            sum_grouped_priority("ab\ncd", 2)
+        );
+        assert_eq!(
+            Err(RucksackError::SeveralCommonItems {
+                group: 1,
+                items: vec![Item(b'b'), Item(b'c')],
+            }),
+            sum_grouped_priority("abc\nbcd", 2)
+        );
+        assert_eq!(
+            Err(RucksackError::NoSharedItem { line: 1 }),
+            sum_shared_priority("abcd")
+        );
+        assert_eq!(
+            Err(RucksackError::SeveralSharedItems {
+                line: 2,
+                items: vec![Item(b'a'), Item(b'B')],
+            }),
+            sum_shared_priority("aa\naBBa")
+        );
+        assert_eq!(
+            "Group 1 has b, c in common",
+            RucksackError::SeveralCommonItems {
+                group: 1,
+                items: vec![Item(b'b'), Item(b'c')],
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_requested_group_size() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(3, requested_group_size(args(&["--variant", "all"])));
+        assert_eq!(4, requested_group_size(args(&["--group-size", "4"])));
+    }
 }
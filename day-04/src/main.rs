@@ -12,26 +12,419 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use aoc::variants::Variants;
+use coverage::*;
 use std::collections::HashSet;
-
+use std::env::args;
+use std::fmt;
 use std::fs::read_to_string;
+use std::process::exit;
+use std::str::FromStr;
+
+#[cfg(not(tarpaulin_include))]
+fn or_exit<T>(result: Result<T, LineError>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        exit(1);
+    })
+}
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
     let input = read_to_string("input.txt").expect("Unable to read input file");
-    println!("Part 1: {}", count_overlaps(&input));
-    println!("Part 2: {}", count_intersections(&input));
+    let args = args().collect::<Vec<String>>();
+    if let Some(index) = args.iter().position(|arg| "--coverage" == arg) {
+        let more_than = args
+            .get(index + 1)
+            .map(|count| count.parse::<usize>().expect("--coverage needs a number"))
+            .unwrap_or(1);
+        print_coverage(&or_exit(parse_groups(&input)), more_than);
+        return;
+    }
+    Variants::new()
+        .register(1, "interval", |input| {
+            count_overlaps(&or_exit(parse_groups(input))).to_string()
+        })
+        .register(1, "hashset", |input| {
+            count_overlaps_hashset(input).to_string()
+        })
+        .register(2, "interval", |input| {
+            count_intersections(&or_exit(parse_groups(input))).to_string()
+        })
+        .register(2, "hashset", |input| {
+            count_intersections_hashset(input).to_string()
+        })
+        .run_from_args(&input);
+}
+
+#[cfg(not(tarpaulin_include))]
+fn print_coverage(groups: &[AssignmentGroup], more_than: usize) {
+    let assignments = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, group)| {
+            group
+                .intervals
+                .iter()
+                .map(move |interval| (index, *interval))
+        })
+        .collect::<Vec<(usize, Interval)>>();
+    let intervals = assignments
+        .iter()
+        .map(|(_, interval)| *interval)
+        .collect::<Vec<Interval>>();
+    let covered = IntervalSet::new(intervals.iter().copied());
+    println!(
+        "Sections covered: {} in {} stretches",
+        covered.sections(),
+        covered.intervals().len()
+    );
+    println!("Gaps: {}", IntervalSet::new(covered.gaps()));
+    let crowded = covered_by_more_than(&intervals, more_than);
+    println!(
+        "Covered by more than {} elves: {} sections in {}",
+        more_than,
+        crowded.sections(),
+        crowded
+    );
+    if let Some((interval, depth)) = most_overlapped(&intervals) {
+        println!("Most overlapped: {} by {} elves", interval, depth);
+    }
+    println!(
+        "Overlapping pairs within lines: {}",
+        groups
+            .iter()
+            .map(AssignmentGroup::overlapping_pairs)
+            .sum::<usize>()
+    );
+    println!(
+        "Overlapping pairs across lines: {}",
+        overlapping_pairs(&assignments).len()
+    );
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AssignmentError {
+    MissingDash(String),
+    InvalidSection(String),
+    Reversed { start: u64, end: u64 },
+}
+
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssignmentError::MissingDash(range) => {
+                write!(f, "{:?} is not a start-end range", range)
+            }
+            AssignmentError::InvalidSection(section) => {
+                write!(f, "{:?} is not a section number", section)
+            }
+            AssignmentError::Reversed { start, end } => {
+                write!(f, "{}-{} ends before it starts", start, end)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct LineError {
+    line: usize,
+    content: String,
+    error: AssignmentError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {} ({:?}): {}", self.line, self.content, self.error)
+    }
+}
+
+// Inclusive on both ends, like the section ranges in the input
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Interval {
+    start: u64,
+    end: u64,
+}
+
+fn parse_section(input: &str) -> Result<u64, AssignmentError> {
+    input
+        .parse::<u64>()
+        .map_err(|_| AssignmentError::InvalidSection(input.to_string()))
+}
+
+impl FromStr for Interval {
+    type Err = AssignmentError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (start, end) = input
+            .split_once('-')
+            .ok_or_else(|| AssignmentError::MissingDash(input.to_string()))?;
+        let start = parse_section(start)?;
+        let end = parse_section(end)?;
+        Interval::new(start, end).ok_or(AssignmentError::Reversed { start, end })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl Interval {
+    fn new(start: u64, end: u64) -> Option<Interval> {
+        (start <= end).then_some(Interval { start, end })
+    }
+
+    // Saturates for the full u64 range, which has one section too many to count
+    fn sections(&self) -> u64 {
+        (self.end - self.start).saturating_add(1)
+    }
+
+    // Absorbing the other interval leaves this one unchanged
+    fn contains(&self, other: &Interval) -> bool {
+        Some(*self) == self.union(other)
+    }
+
+    fn overlaps(&self, other: &Interval) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    fn intersection(&self, other: &Interval) -> Option<Interval> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    // Only defined when the result is a single interval, so touching ends merge
+    fn union(&self, other: &Interval) -> Option<Interval> {
+        if self.start.saturating_sub(1) <= other.end && other.start.saturating_sub(1) <= self.end {
+            Interval::new(self.start.min(other.start), self.end.max(other.end))
+        } else {
+            None
+        }
+    }
+}
+
+// Any number of elves; a lone range contains itself and overlaps nothing
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct AssignmentGroup {
+    intervals: Vec<Interval>,
+}
+
+impl FromStr for AssignmentGroup {
+    type Err = AssignmentError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(AssignmentGroup {
+            intervals: input
+                .trim()
+                .split(',')
+                .map(str::parse::<Interval>)
+                .collect::<Result<Vec<Interval>, AssignmentError>>()?,
+        })
+    }
+}
+
+impl AssignmentGroup {
+    // Only an interval spanning the lowest start and the highest end can hold the rest
+    fn fully_contained(&self) -> bool {
+        let hull = self
+            .intervals
+            .iter()
+            .copied()
+            .reduce(|hull, interval| Interval {
+                start: hull.start.min(interval.start),
+                end: hull.end.max(interval.end),
+            });
+        hull.is_some_and(|hull| {
+            self.intervals
+                .iter()
+                .any(|interval| interval.contains(&hull))
+        })
+    }
+
+    // Sorted by start, any overlap shows up between neighbours
+    fn overlapping(&self) -> bool {
+        let mut sorted = self.intervals.clone();
+        sorted.sort_by_key(|interval| interval.start);
+        sorted.windows(2).any(|pair| pair[0].overlaps(&pair[1]))
+    }
+
+    fn overlapping_pairs(&self) -> usize {
+        let assignments = self
+            .intervals
+            .iter()
+            .copied()
+            .enumerate()
+            .collect::<Vec<(usize, Interval)>>();
+        overlapping_pairs(&assignments).len()
+    }
+}
+
+fn parse_groups(input: &str) -> Result<Vec<AssignmentGroup>, LineError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            line.parse::<AssignmentGroup>().map_err(|error| LineError {
+                line: index + 1,
+                content: line.trim().to_string(),
+                error,
+            })
+        })
+        .collect()
+}
+
+fn count_overlaps(groups: &[AssignmentGroup]) -> u32 {
+    groups
+        .iter()
+        .filter(|group| group.fully_contained())
+        .count() as u32
+}
+
+fn count_intersections(groups: &[AssignmentGroup]) -> u32 {
+    groups.iter().filter(|group| group.overlapping()).count() as u32
+}
+
+mod coverage {
+    use super::*;
+
+    // Sorted, disjoint and never touching, so every gap is at least one section
+    #[derive(Debug, PartialEq, Eq, Default)]
+    pub struct IntervalSet {
+        intervals: Vec<Interval>,
+    }
+
+    impl IntervalSet {
+        pub fn new(intervals: impl IntoIterator<Item = Interval>) -> IntervalSet {
+            let mut sorted = intervals.into_iter().collect::<Vec<Interval>>();
+            sorted.sort_by_key(|interval| interval.start);
+            let mut merged: Vec<Interval> = Vec::with_capacity(sorted.len());
+            for interval in sorted {
+                match merged.last_mut().and_then(|last| last.union(&interval)) {
+                    Some(union) => *merged.last_mut().unwrap() = union,
+                    None => merged.push(interval),
+                }
+            }
+            IntervalSet { intervals: merged }
+        }
+
+        pub fn intervals(&self) -> &[Interval] {
+            &self.intervals
+        }
+
+        pub fn sections(&self) -> u64 {
+            self.intervals.iter().fold(0u64, |total, interval| {
+                total.saturating_add(interval.sections())
+            })
+        }
+
+        pub fn gaps(&self) -> Vec<Interval> {
+            self.intervals
+                .windows(2)
+                .map(|pair| Interval {
+                    start: pair[0].end + 1,
+                    end: pair[1].start - 1,
+                })
+                .collect()
+        }
+    }
+
+    impl fmt::Display for IntervalSet {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            if self.intervals.is_empty() {
+                return write!(f, "none");
+            }
+            let intervals = self
+                .intervals
+                .iter()
+                .map(|interval| interval.to_string())
+                .collect::<Vec<String>>();
+            write!(f, "{}", intervals.join(", "))
+        }
+    }
+
+    // Sweeps start and end events to find every stretch covered by the same number of elves
+    pub fn depth_profile(intervals: &[Interval]) -> Vec<(Interval, usize)> {
+        let mut events = Vec::with_capacity(intervals.len() * 2);
+        for interval in intervals {
+            events.push((interval.start, 1i64));
+            if let Some(after) = interval.end.checked_add(1) {
+                events.push((after, -1));
+            }
+        }
+        events.sort_unstable();
+        let mut profile = Vec::new();
+        let mut depth = 0i64;
+        let mut index = 0;
+        while index < events.len() {
+            let position = events[index].0;
+            while index < events.len() && position == events[index].0 {
+                depth += events[index].1;
+                index += 1;
+            }
+            if 0 < depth {
+                let end = events.get(index).map_or(u64::MAX, |(next, _)| next - 1);
+                profile.push((
+                    Interval {
+                        start: position,
+                        end,
+                    },
+                    depth as usize,
+                ));
+            }
+        }
+        profile
+    }
+
+    pub fn covered_by_more_than(intervals: &[Interval], count: usize) -> IntervalSet {
+        IntervalSet::new(
+            depth_profile(intervals)
+                .into_iter()
+                .filter(|(_, depth)| *depth > count)
+                .map(|(interval, _)| interval),
+        )
+    }
+
+    // Ties go to the lowest stretch of sections
+    pub fn most_overlapped(intervals: &[Interval]) -> Option<(Interval, usize)> {
+        depth_profile(intervals)
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, depth)| *depth)
+    }
+
+    // Pairs of assignments on different lines that share a section, as indices into
+    // assignments. Only intervals still open at the current start stay active, so the
+    // work grows with the number of overlaps rather than the square of the input.
+    pub fn overlapping_pairs(assignments: &[(usize, Interval)]) -> Vec<(usize, usize)> {
+        let mut order = (0..assignments.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|index| assignments[*index].1.start);
+        let mut active: Vec<usize> = Vec::new();
+        let mut pairs = Vec::new();
+        for index in order {
+            let (line, interval) = assignments[index];
+            active.retain(|other| assignments[*other].1.end >= interval.start);
+            for other in active.iter() {
+                if assignments[*other].0 != line {
+                    pairs.push(((*other).min(index), (*other).max(index)));
+                }
+            }
+            active.push(index);
+        }
+        pairs.sort_unstable();
+        pairs
+    }
 }
 
-fn count_overlaps(input: &str) -> u32 {
+fn count_overlaps_hashset(input: &str) -> u32 {
     let mut count: u32 = 0;
     for line in input.trim().lines() {
         let line = line.trim();
-        let groups: Vec<&str> = line.split(",").collect();
-        let first_group = groups[0].split("-").collect::<Vec<&str>>();
+        let groups: Vec<&str> = line.split(',').collect();
+        let first_group = groups[0].split('-').collect::<Vec<&str>>();
         let first: HashSet<u32> =
             (first_group[0].parse().unwrap()..=first_group[1].parse().unwrap()).collect();
-        let second_group = groups[1].split("-").collect::<Vec<&str>>();
+        let second_group = groups[1].split('-').collect::<Vec<&str>>();
         let second: HashSet<u32> =
             (second_group[0].parse().unwrap()..=second_group[1].parse().unwrap()).collect();
         if first.is_subset(&second) || second.is_subset(&first) {
@@ -41,15 +434,15 @@ fn count_overlaps(input: &str) -> u32 {
     count
 }
 
-fn count_intersections(input: &str) -> u32 {
+fn count_intersections_hashset(input: &str) -> u32 {
     let mut count: u32 = 0;
     for line in input.trim().lines() {
         let line = line.trim();
-        let groups: Vec<&str> = line.split(",").collect();
-        let first_group = groups[0].split("-").collect::<Vec<&str>>();
+        let groups: Vec<&str> = line.split(',').collect();
+        let first_group = groups[0].split('-').collect::<Vec<&str>>();
         let first: HashSet<u32> =
             (first_group[0].parse().unwrap()..=first_group[1].parse().unwrap()).collect();
-        let second_group = groups[1].split("-").collect::<Vec<&str>>();
+        let second_group = groups[1].split('-').collect::<Vec<&str>>();
         let second: HashSet<u32> =
             (second_group[0].parse().unwrap()..=second_group[1].parse().unwrap()).collect();
         if 0 < first.intersection(&second).count() {
@@ -62,20 +455,280 @@ fn count_intersections(input: &str) -> u32 {
 #[cfg(not(tarpaulin_include))]
 #[cfg(test)]
 mod tests {
+    use super::coverage::*;
     use super::*;
 
+    fn interval(start: u64, end: u64) -> Interval {
+        Interval::new(start, end).unwrap()
+    }
+
+    #[test]
+    fn test_interval_set() {
+        let set = IntervalSet::new([
+            interval(10, 12),
+            interval(2, 4),
+            interval(3, 6),
+            interval(7, 7),
+            interval(20, 20),
+        ]);
+        assert_eq!(
+            &[interval(2, 7), interval(10, 12), interval(20, 20)],
+            set.intervals()
+        );
+        assert_eq!(6 + 3 + 1, set.sections());
+        assert_eq!(vec![interval(8, 9), interval(13, 19)], set.gaps());
+        assert_eq!("2-7, 10-12, 20-20", set.to_string());
+        assert_eq!("none", IntervalSet::default().to_string());
+        assert_eq!(
+            u64::MAX,
+            IntervalSet::new([interval(0, u64::MAX)]).sections()
+        );
+    }
+
+    #[test]
+    fn test_depth_profile() {
+        let intervals = [
+            interval(2, 4),
+            interval(3, 6),
+            interval(4, 8),
+            interval(10, 10),
+        ];
+        assert_eq!(
+            vec![
+                (interval(2, 2), 1),
+                (interval(3, 3), 2),
+                (interval(4, 4), 3),
+                (interval(5, 6), 2),
+                (interval(7, 8), 1),
+                (interval(10, 10), 1),
+            ],
+            depth_profile(&intervals)
+        );
+        assert_eq!(
+            vec![(interval(5, u64::MAX), 2)],
+            depth_profile(&[interval(5, u64::MAX), interval(5, u64::MAX)])
+        );
+        assert_eq!(
+            IntervalSet::new([interval(3, 6)]),
+            covered_by_more_than(&intervals, 1)
+        );
+        assert_eq!(IntervalSet::default(), covered_by_more_than(&intervals, 3));
+        assert_eq!(Some((interval(4, 4), 3)), most_overlapped(&intervals));
+        assert_eq!(
+            Some((interval(1, 1), 1)),
+            most_overlapped(&[interval(3, 3), interval(1, 1)])
+        );
+        assert_eq!(None, most_overlapped(&[]));
+    }
+
+    #[test]
+    fn test_overlapping_pairs() {
+        let assignments = [
+            (0, interval(2, 4)),
+            (0, interval(3, 5)),
+            (1, interval(5, 7)),
+            (2, interval(1, 2)),
+            (3, interval(9, 9)),
+        ];
+        assert_eq!(vec![(0, 3), (1, 2)], overlapping_pairs(&assignments));
+        let brute_force = (0..assignments.len())
+            .flat_map(|first| (first + 1..assignments.len()).map(move |second| (first, second)))
+            .filter(|(first, second)| {
+                assignments[*first].0 != assignments[*second].0
+                    && assignments[*first].1.overlaps(&assignments[*second].1)
+            })
+            .collect::<Vec<(usize, usize)>>();
+        assert_eq!(brute_force, overlapping_pairs(&assignments));
+    }
+
+    #[test]
+    fn test_interval_from_str() {
+        assert_eq!(Interval::new(2, 4), "2-4".parse().ok());
+        assert_eq!(Interval::new(6, 6), " 6-6 ".parse().ok());
+        assert_eq!(Interval::new(1, 4000000000), "1-4000000000".parse().ok());
+        assert_eq!(
+            Err(AssignmentError::Reversed { start: 7, end: 3 }),
+            "7-3".parse::<Interval>()
+        );
+        assert_eq!(
+            Err(AssignmentError::MissingDash("7".to_string())),
+            "7".parse::<Interval>()
+        );
+        assert_eq!(
+            Err(AssignmentError::InvalidSection("a".to_string())),
+            "a-3".parse::<Interval>()
+        );
+        assert_eq!(
+            Err(AssignmentError::InvalidSection("3-4".to_string())),
+            "2-3-4".parse::<Interval>()
+        );
+    }
+
+    #[test]
+    fn test_interval_contains() {
+        let interval = Interval::new(2, 8).unwrap();
+        assert!(interval.contains(&Interval::new(3, 7).unwrap()));
+        assert!(interval.contains(&interval));
+        assert!(!interval.contains(&Interval::new(1, 7).unwrap()));
+        assert!(!Interval::new(3, 7).unwrap().contains(&interval));
+    }
+
+    #[test]
+    fn test_interval_overlaps() {
+        let interval = Interval::new(5, 7).unwrap();
+        assert!(interval.overlaps(&Interval::new(7, 9).unwrap()));
+        assert!(interval.overlaps(&Interval::new(1, 5).unwrap()));
+        assert!(interval.overlaps(&Interval::new(6, 6).unwrap()));
+        assert!(!interval.overlaps(&Interval::new(8, 9).unwrap()));
+        assert!(!interval.overlaps(&Interval::new(1, 4).unwrap()));
+    }
+
+    #[test]
+    fn test_interval_intersection() {
+        let interval = Interval::new(2, 6).unwrap();
+        assert_eq!(
+            Interval::new(4, 6),
+            interval.intersection(&Interval::new(4, 8).unwrap())
+        );
+        assert_eq!(None, interval.intersection(&Interval::new(7, 8).unwrap()));
+    }
+
+    #[test]
+    fn test_interval_union() {
+        let interval = Interval::new(2, 6).unwrap();
+        assert_eq!(
+            Interval::new(2, 8),
+            interval.union(&Interval::new(4, 8).unwrap())
+        );
+        assert_eq!(
+            Interval::new(2, 8),
+            interval.union(&Interval::new(7, 8).unwrap())
+        );
+        assert_eq!(None, interval.union(&Interval::new(8, 9).unwrap()));
+        assert_eq!(
+            Interval::new(0, u64::MAX),
+            Interval::new(0, 0)
+                .unwrap()
+                .union(&Interval::new(1, u64::MAX).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_wide_ranges() {
+        let input = "1-4000000000,2-3999999999
+            1-4000000000,4000000000-8000000000
+            1-2,3-4000000000";
+        let pairs = parse_groups(input).unwrap();
+        assert_eq!(1, count_overlaps(&pairs));
+        assert_eq!(2, count_intersections(&pairs));
+    }
+
+    #[test]
+    fn test_variants_agree() {
+        let input = "2-4,6-8
+            2-3,4-5
+            5-7,7-9
+            2-8,3-7
+            6-6,4-6
+            2-6,4-8";
+        let pairs = parse_groups(input).unwrap();
+        assert_eq!(count_overlaps_hashset(input), count_overlaps(&pairs));
+        assert_eq!(
+            count_intersections_hashset(input),
+            count_intersections(&pairs)
+        );
+    }
+
+    #[test]
+    fn test_assignment_group_from_str() {
+        assert_eq!(
+            Ok(AssignmentGroup {
+                intervals: vec![interval(2, 4), interval(6, 8)],
+            }),
+            " 2-4,6-8 ".parse::<AssignmentGroup>()
+        );
+        assert_eq!(
+            Ok(AssignmentGroup {
+                intervals: vec![interval(2, 4)],
+            }),
+            "2-4".parse::<AssignmentGroup>()
+        );
+        assert_eq!(
+            Ok(AssignmentGroup {
+                intervals: vec![interval(2, 4), interval(6, 8), interval(1, 1)],
+            }),
+            "2-4,6-8,1-1".parse::<AssignmentGroup>()
+        );
+        assert_eq!(
+            Err(AssignmentError::Reversed { start: 8, end: 6 }),
+            "2-4,8-6".parse::<AssignmentGroup>()
+        );
+        assert_eq!(
+            Err(AssignmentError::MissingDash("".to_string())),
+            "2-4,".parse::<AssignmentGroup>()
+        );
+    }
+
+    #[test]
+    fn test_assignment_group_queries() {
+        let group = |input: &str| input.parse::<AssignmentGroup>().unwrap();
+        assert!(group("1-9,2-3,4-9,1-1").fully_contained());
+        assert!(group("3-3,3-3,3-3").fully_contained());
+        assert!(!group("1-8,2-9,3-4").fully_contained());
+        assert!(group("1-2,7-9,3-4,4-5").overlapping());
+        assert!(group("1-9,3-3,5-5").overlapping());
+        assert!(!group("7-9,1-2,3-4,5-6").overlapping());
+        assert_eq!(0, group("7-9,1-2,3-4,5-6").overlapping_pairs());
+        assert_eq!(3, group("1-9,3-3,5-5,4-4").overlapping_pairs());
+        assert_eq!(6, group("1-4,2-5,3-6,4-7").overlapping_pairs());
+        assert!(group("2-4").fully_contained());
+        assert!(!group("2-4").overlapping());
+        assert_eq!(0, group("2-4").overlapping_pairs());
+        for (input, contained, overlapping) in [
+            ("2-4,6-8", false, false),
+            ("5-7,7-9", false, true),
+            ("2-8,3-7", true, true),
+            ("6-6,4-6", true, true),
+        ] {
+            assert_eq!(contained, group(input).fully_contained());
+            assert_eq!(overlapping, group(input).overlapping());
+            assert_eq!(overlapping as usize, group(input).overlapping_pairs());
+        }
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!(2, parse_groups("2-4,6-8\n\n2-3,4-5\n").unwrap().len());
+        let error = parse_groups("2-4,6-8\n\n  7-3,1-2").unwrap_err();
+        assert_eq!(
+            LineError {
+                line: 3,
+                content: "7-3,1-2".to_string(),
+                error: AssignmentError::Reversed { start: 7, end: 3 },
+            },
+            error
+        );
+        assert_eq!(
+            "Line 3 (\"7-3,1-2\"): 7-3 ends before it starts",
+            error.to_string()
+        );
+    }
+
     #[test]
     fn test_count_overlaps() {
         assert_eq!(
             2,
             count_overlaps(
-                "2-4,6-8
+                &parse_groups(
+                    "2-4,6-8
                 2-3,4-5
                 5-7,7-9
                 2-8,3-7
                 6-6,4-6
                 2-6,4-8
                 "
+                )
+                .unwrap()
             )
         );
     }
@@ -85,13 +738,16 @@ mod tests {
         assert_eq!(
             4,
             count_intersections(
-                "2-4,6-8
+                &parse_groups(
+                    "2-4,6-8
                 2-3,4-5
                 5-7,7-9
                 2-8,3-7
                 6-6,4-6
                 2-6,4-8
                 "
+                )
+                .unwrap()
             )
         );
     }
@@ -12,144 +12,917 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use aoc::variants::Variants;
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::env::args;
+use std::fmt;
 use std::fs::read_to_string;
+use std::process::exit;
 
 lazy_static! {
     static ref MOVE_PATTERN: Regex =
-        Regex::new(r"move (?P<count>\d+) from (?P<start>\d+) to (?P<end>\d+)").unwrap();
-}
-
-// I don't feel like messing with the regex for parsing this
-// Here's my input stacks
-//                         [R] [J] [W]
-//             [R] [N]     [T] [T] [C]
-// [R]         [P] [G]     [J] [P] [T]
-// [Q]     [C] [M] [V]     [F] [F] [H]
-// [G] [P] [M] [S] [Z]     [Z] [C] [Q]
-// [P] [C] [P] [Q] [J] [J] [P] [H] [Z]
-// [C] [T] [H] [T] [H] [P] [G] [L] [V]
-// [F] [W] [B] [L] [P] [D] [L] [N] [G]
-//  1   2   3   4   5   6   7   8   9
-#[cfg(not(tarpaulin_include))]
-fn main() {
-    let stacks = vec![
-        vec!['F', 'C', 'P', 'G', 'Q', 'R'],
-        vec!['W', 'T', 'C', 'P'],
-        vec!['B', 'H', 'P', 'M', 'C'],
-        vec!['L', 'T', 'Q', 'S', 'M', 'P', 'R'],
-        vec!['P', 'H', 'J', 'Z', 'V', 'G', 'N'],
-        vec!['D', 'P', 'J'],
-        vec!['L', 'G', 'P', 'Z', 'F', 'J', 'T', 'R'],
-        vec!['N', 'L', 'H', 'C', 'F', 'P', 'T', 'J'],
-        vec!['G', 'V', 'Z', 'Q', 'H', 'T', 'C', 'W'],
-    ];
-    let input = read_to_string("input.txt").expect("Unable to read input file");
-    println!(
-        "Part 1: {}",
-        determine_final_stack_tops(&input, stacks.clone())
-    );
-    println!(
-        "Part 2: {}",
-        determine_final_stack_tops_9001(&input, stacks.clone())
-    );
+        Regex::new(r"^move (?P<count>\d+) from (?P<start>\d+) to (?P<end>\d+)$").unwrap();
 }
 
-fn determine_final_stack_tops(input: &str, stacks: Vec<Vec<char>>) -> String {
-    let mut stacks = stacks;
-    for line in input.trim().lines() {
-        if let Some(captures) = MOVE_PATTERN.captures(line) {
-            let count = captures["count"].parse::<usize>().unwrap();
-            let start = captures["start"].parse::<usize>().unwrap();
-            let end = captures["end"].parse::<usize>().unwrap();
-            let mut start_stack = stacks.get(start - 1).unwrap().clone();
-            let mut end_stack = stacks.get(end - 1).unwrap().clone();
-            for _ in 0..count {
-                end_stack.push(start_stack.pop().unwrap());
+// Each stack is listed bottom first, so the top crate is the last one
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Stacks(Vec<Vec<char>>);
+
+impl Stacks {
+    // The drawing is everything above the first blank line, ending with the numbered
+    // footer. Crate letters sit at every fourth column, so a missing crate is blank.
+    fn parse(input: &str) -> Result<Stacks, MoveError> {
+        let drawing = input
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .take_while(|line| !line.trim().is_empty())
+            .collect::<Vec<&str>>();
+        let (footer, rows) = drawing.split_last().ok_or(MoveError::MissingDrawing)?;
+        let numbers = footer.split_whitespace().collect::<Vec<&str>>();
+        if numbers
+            .iter()
+            .enumerate()
+            .any(|(index, number)| (index + 1).to_string() != *number)
+        {
+            return Err(MoveError::MissingDrawing);
+        }
+        let mut stacks = vec![Vec::new(); numbers.len()];
+        for row in rows.iter().rev() {
+            let row = row.as_bytes();
+            for (index, stack) in stacks.iter_mut().enumerate() {
+                match row.get(index * 4 + 1) {
+                    Some(byte) if byte.is_ascii_alphabetic() => stack.push(*byte as char),
+                    _ => {}
+                }
+            }
+        }
+        Ok(Stacks(stacks))
+    }
+
+    // Both stacks must exist and the one crates are lifted from must hold enough
+    fn check(&self, step: &Move, source: usize) -> Result<(), MoveError> {
+        for stack in [step.start, step.end] {
+            if 0 == stack || stack > self.0.len() {
+                return Err(MoveError::UnknownStack {
+                    line: step.line,
+                    stack,
+                });
             }
-            stacks[start - 1] = start_stack;
-            stacks[end - 1] = end_stack;
         }
+        let available = self.0[source - 1].len();
+        if step.count > available {
+            return Err(MoveError::NotEnoughCrates {
+                line: step.line,
+                stack: source,
+                count: step.count,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    // Crates lifted off a stack and put back on it land where they started, whatever
+    // the crane, so a move onto the same stack leaves it unchanged
+    fn apply(&mut self, crane: &impl Crane, step: &Move) -> Result<(), MoveError> {
+        self.check(step, step.start)?;
+        if step.start == step.end {
+            return Ok(());
+        }
+        let source = &mut self.0[step.start - 1];
+        let mut crates = source.split_off(source.len() - step.count);
+        crane.arrange(&mut crates);
+        self.0[step.end - 1].extend(crates);
+        Ok(())
+    }
+
+    // Runs a move backwards, lifting the crates off the destination
+    fn revert(&mut self, crane: &impl Crane, step: &Move) -> Result<(), MoveError> {
+        self.check(step, step.end)?;
+        if step.start == step.end {
+            return Ok(());
+        }
+        let destination = &mut self.0[step.end - 1];
+        let mut crates = destination.split_off(destination.len() - step.count);
+        crane.restore(&mut crates);
+        self.0[step.start - 1].extend(crates);
+        Ok(())
     }
-    let mut final_stack_tops = String::new();
-    for mut stack in stacks {
-        final_stack_tops.push(stack.pop().unwrap().clone());
+
+    fn tops(&self) -> String {
+        self.0
+            .iter()
+            .map(|stack| stack.last().copied().unwrap_or(' '))
+            .collect()
+    }
+}
+
+// Renders the puzzle's drawing, padded to full width so it parses back unchanged
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(item) => format!("[{}]", item),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<String>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        let footer = (1..=self.0.len())
+            .map(|number| format!(" {} ", number))
+            .collect::<Vec<String>>();
+        write!(f, "{}", footer.join(" "))
     }
-    final_stack_tops
 }
 
-fn determine_final_stack_tops_9001(input: &str, stacks: Vec<Vec<char>>) -> String {
-    let mut stacks = stacks;
-    for line in input.trim().lines() {
-        if let Some(captures) = MOVE_PATTERN.captures(line) {
-            let count = captures["count"].parse::<usize>().unwrap();
-            let start = captures["start"].parse::<usize>().unwrap();
-            let end = captures["end"].parse::<usize>().unwrap();
-            let mut start_stack = stacks.get(start - 1).unwrap().clone();
-            let mut end_stack = stacks.get(end - 1).unwrap().clone();
-            let mut temp_stack: Vec<char> = Vec::new();
-            for _ in 0..count {
-                temp_stack.push(start_stack.pop().unwrap());
+trait Crane {
+    // Puts crates lifted off the top of a stack, listed bottom first, into the order
+    // they land on the destination
+    fn arrange(&self, crates: &mut [char]);
+
+    // Undoes arrange, putting crates taken back off the destination into the order
+    // they had on the source
+    fn restore(&self, crates: &mut [char]);
+}
+
+// Moves one crate at a time, so the lifted crates land upside down
+#[derive(Debug, Clone, Copy)]
+struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn arrange(&self, crates: &mut [char]) {
+        crates.reverse();
+    }
+
+    fn restore(&self, crates: &mut [char]) {
+        crates.reverse();
+    }
+}
+
+// Moves the whole batch at once, keeping its order
+#[derive(Debug, Clone, Copy)]
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn arrange(&self, _crates: &mut [char]) {}
+
+    fn restore(&self, _crates: &mut [char]) {}
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Move {
+    line: usize,
+    count: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Move {
+    // Takes the line number along with the text, since every error points at it
+    fn parse(line: usize, content: &str) -> Result<Move, MoveError> {
+        let invalid = || MoveError::InvalidMove {
+            line,
+            content: content.trim().to_string(),
+        };
+        let captures = MOVE_PATTERN.captures(content.trim()).ok_or_else(invalid)?;
+        let number = |name: &str| captures[name].parse::<usize>().map_err(|_| invalid());
+        Ok(Move {
+            line,
+            count: number("count")?,
+            start: number("start")?,
+            end: number("end")?,
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.count, self.start, self.end)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum MoveError {
+    MissingDrawing,
+    InvalidMove {
+        line: usize,
+        content: String,
+    },
+    UnknownStack {
+        line: usize,
+        stack: usize,
+    },
+    NotEnoughCrates {
+        line: usize,
+        stack: usize,
+        count: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::MissingDrawing => {
+                write!(f, "Expected a crate drawing ending in numbered stacks")
             }
-            for _ in 0..count {
-                end_stack.push(temp_stack.pop().unwrap());
+            MoveError::InvalidMove { line, content } => {
+                write!(f, "Line {}: {:?} is not a move", line, content)
             }
-            stacks[start - 1] = start_stack;
-            stacks[end - 1] = end_stack;
+            MoveError::UnknownStack { line, stack } => {
+                write!(f, "Line {}: there is no stack {}", line, stack)
+            }
+            MoveError::NotEnoughCrates {
+                line,
+                stack,
+                count,
+                available,
+            } => write!(
+                f,
+                "Line {}: cannot move {} crates from stack {}, which holds {}",
+                line, count, stack, available
+            ),
         }
     }
-    let mut final_stack_tops = String::new();
-    for mut stack in stacks {
-        final_stack_tops.push(stack.pop().unwrap().clone());
+}
+
+// Moves start after the blank line that ends the drawing
+fn parse_moves(input: &str) -> Result<Vec<Move>, MoveError> {
+    input
+        .lines()
+        .enumerate()
+        .skip_while(|(_, line)| line.trim().is_empty())
+        .skip_while(|(_, line)| !line.trim().is_empty())
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| Move::parse(index + 1, line))
+        .collect()
+}
+
+// Applied moves can be undone one at a time, so any step can be revisited
+#[derive(Debug, Clone)]
+struct History<C: Crane> {
+    stacks: Stacks,
+    moves: Vec<Move>,
+    applied: usize,
+    crane: C,
+}
+
+impl<C: Crane> History<C> {
+    fn new(input: &str, crane: C) -> Result<History<C>, MoveError> {
+        Ok(History {
+            stacks: Stacks::parse(input)?,
+            moves: parse_moves(input)?,
+            applied: 0,
+            crane,
+        })
+    }
+
+    fn step(&mut self) -> Result<bool, MoveError> {
+        let step = match self.moves.get(self.applied) {
+            Some(step) => *step,
+            None => return Ok(false),
+        };
+        self.stacks.apply(&self.crane, &step)?;
+        self.applied += 1;
+        Ok(true)
+    }
+
+    fn undo(&mut self) -> bool {
+        if 0 == self.applied {
+            return false;
+        }
+        self.applied -= 1;
+        let step = self.moves[self.applied];
+        self.stacks
+            .revert(&self.crane, &step)
+            .expect("Unable to undo an applied move");
+        true
+    }
+
+    // Stops at the last move when asked for a step past the end
+    fn replay_to(&mut self, step: usize) -> Result<(), MoveError> {
+        while self.applied > step && self.undo() {}
+        while self.applied < step && self.step()? {}
+        Ok(())
+    }
+
+    fn run(&mut self) -> Result<(), MoveError> {
+        self.replay_to(self.moves.len())
+    }
+}
+
+// The initial drawing, then the drawing after every nth move and after the last one
+fn dump(input: &str, crane: impl Crane, every: usize) -> Result<String, MoveError> {
+    let mut history = History::new(input, crane)?;
+    let mut output = format!("Initial:\n{}\n", history.stacks);
+    while history.step()? {
+        if history.applied % every.max(1) == 0 || history.applied == history.moves.len() {
+            let step = history.moves[history.applied - 1];
+            output.push_str(&format!(
+                "\nAfter move {} (line {}, {}):\n{}\n",
+                history.applied, step.line, step, history.stacks
+            ));
+        }
+    }
+    Ok(output)
+}
+
+// Treats the drawing as the final state and runs the moves backwards to find the
+// drawing they started from
+fn reverse(input: &str, crane: impl Crane) -> Result<Stacks, MoveError> {
+    let mut stacks = Stacks::parse(input)?;
+    for step in parse_moves(input)?.iter().rev() {
+        stacks.revert(&crane, step)?;
+    }
+    Ok(stacks)
+}
+
+fn run_crane(input: &str, crane: impl Crane) -> Result<String, MoveError> {
+    let mut history = History::new(input, crane)?;
+    history.run()?;
+    Ok(history.stacks.tops())
+}
+
+#[cfg(any(feature = "explorer", test))]
+mod simulation {
+    use super::*;
+    use aoc::explorer::Simulation;
+
+    #[derive(Debug, Clone)]
+    pub struct CrateSimulation<C: Crane + Clone> {
+        history: History<C>,
+        error: Option<MoveError>,
+    }
+
+    impl<C: Crane + Clone> CrateSimulation<C> {
+        pub fn new(input: &str, crane: C) -> Result<CrateSimulation<C>, MoveError> {
+            Ok(CrateSimulation {
+                history: History::new(input, crane)?,
+                error: None,
+            })
+        }
+    }
+
+    impl<C: Crane + Clone> Simulation for CrateSimulation<C> {
+        fn step(&mut self) -> bool {
+            match self.history.step() {
+                Ok(stepped) => stepped,
+                Err(error) => {
+                    self.error = Some(error);
+                    false
+                }
+            }
+        }
+
+        fn fields(&self) -> Vec<(String, i64)> {
+            let mut fields = vec![
+                ("move".to_string(), self.history.applied as i64),
+                (
+                    "remaining".to_string(),
+                    (self.history.moves.len() - self.history.applied) as i64,
+                ),
+            ];
+            for (index, stack) in self.history.stacks.0.iter().enumerate() {
+                fields.push((format!("stack{}.height", index + 1), stack.len() as i64));
+            }
+            fields
+        }
+
+        fn panes(&self) -> Vec<(String, Vec<String>)> {
+            let stacks = self
+                .history
+                .stacks
+                .0
+                .iter()
+                .enumerate()
+                .map(|(index, stack)| {
+                    format!(
+                        "{}: {}",
+                        index + 1,
+                        stack
+                            .iter()
+                            .map(|item| item.to_string())
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    )
+                })
+                .collect();
+            let next_move = match (&self.error, self.history.moves.get(self.history.applied)) {
+                (Some(error), _) => error.to_string(),
+                (None, Some(step)) => step.to_string(),
+                (None, None) => "done".to_string(),
+            };
+            vec![
+                ("Stacks".to_string(), stacks),
+                ("Next move".to_string(), vec![next_move]),
+            ]
+        }
     }
-    final_stack_tops
+}
+
+#[cfg(not(tarpaulin_include))]
+fn main() {
+    let input = read_to_string("input.txt").expect("Unable to read input file");
+    #[cfg(feature = "explorer")]
+    if let Some(part) = aoc::explorer::requested_part(std::env::args()) {
+        if 2 == part {
+            aoc::explorer::explore(or_exit(simulation::CrateSimulation::new(
+                &input,
+                CrateMover9001,
+            )))
+        } else {
+            aoc::explorer::explore(or_exit(simulation::CrateSimulation::new(
+                &input,
+                CrateMover9000,
+            )))
+        }
+        .expect("Unable to run explorer");
+        return;
+    }
+    let args = args().collect::<Vec<String>>();
+    if let Some(index) = args.iter().position(|arg| "--dump" == arg) {
+        let every = args
+            .get(index + 1)
+            .and_then(|every| every.parse::<usize>().ok())
+            .unwrap_or(1);
+        for (part, output) in [
+            (1, dump(&input, CrateMover9000, every)),
+            (2, dump(&input, CrateMover9001, every)),
+        ] {
+            println!("Part {}:", part);
+            print!("{}", or_exit(output));
+        }
+        return;
+    }
+    if args.iter().any(|arg| "--reverse" == arg) {
+        for (part, stacks) in [
+            (1, reverse(&input, CrateMover9000)),
+            (2, reverse(&input, CrateMover9001)),
+        ] {
+            println!("Part {}:", part);
+            println!("{}", or_exit(stacks));
+        }
+        return;
+    }
+    if let Some(index) = args.iter().position(|arg| "--step" == arg) {
+        let step = args
+            .get(index + 1)
+            .and_then(|step| step.parse::<usize>().ok())
+            .expect("--step needs a move number");
+        println!(
+            "Part 1: {}",
+            or_exit(tops_after(&input, CrateMover9000, step))
+        );
+        println!(
+            "Part 2: {}",
+            or_exit(tops_after(&input, CrateMover9001, step))
+        );
+        return;
+    }
+    Variants::new()
+        .register(1, "in-place", |input| {
+            or_exit(determine_final_stack_tops(input))
+        })
+        .register(2, "in-place", |input| {
+            or_exit(determine_final_stack_tops_9001(input))
+        })
+        .run_from_args(&input);
+}
+
+#[cfg(not(tarpaulin_include))]
+fn or_exit<T>(result: Result<T, MoveError>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        exit(1);
+    })
+}
+
+fn tops_after(input: &str, crane: impl Crane, step: usize) -> Result<String, MoveError> {
+    let mut history = History::new(input, crane)?;
+    history.replay_to(step)?;
+    Ok(history.stacks.tops())
+}
+
+fn determine_final_stack_tops(input: &str) -> Result<String, MoveError> {
+    run_crane(input, CrateMover9000)
+}
+
+fn determine_final_stack_tops_9001(input: &str) -> Result<String, MoveError> {
+    run_crane(input, CrateMover9001)
 }
 
 #[cfg(not(tarpaulin_include))]
 #[cfg(test)]
 mod tests {
+    use super::simulation::*;
     use super::*;
+    use aoc::explorer::Simulation;
+
+    const EXAMPLE: &str = concat!(
+        "    [D]    \n",
+        "[N] [C]    \n",
+        "[Z] [M] [P]\n",
+        " 1   2   3 \n",
+        "\n",
+        "move 1 from 2 to 1\n",
+        "move 3 from 1 to 3\n",
+        "move 2 from 2 to 1\n",
+        "move 1 from 1 to 2\n",
+    );
 
     #[test]
-    fn test_determine_final_stack_top() {
-        let stacks = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+    fn test_parse_stacks() {
+        assert_eq!(
+            Ok(Stacks(vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']])),
+            Stacks::parse(EXAMPLE)
+        );
+        assert_eq!(
+            Ok(Stacks(vec![vec!['A'], vec![], vec!['B', 'C'], vec![]])),
+            Stacks::parse("        [C]\n[A]     [B]\n 1   2   3   4\n")
+        );
+        assert_eq!(
+            Ok(Stacks(vec![Vec::new(); 2])),
+            Stacks::parse("\n 1   2\n\nmove 1 from 1 to 2")
+        );
+        assert_eq!(Err(MoveError::MissingDrawing), Stacks::parse("\n\n"));
+        assert_eq!(
+            Err(MoveError::MissingDrawing),
+            Stacks::parse("move 1 from 1 to 2")
+        );
+        assert_eq!(
+            Err(MoveError::MissingDrawing),
+            History::new("", CrateMover9000).map(|history| history.applied)
+        );
+    }
+
+    #[test]
+    fn test_render_stacks() {
+        let stacks = Stacks::parse(EXAMPLE).unwrap();
+        assert_eq!(EXAMPLE.split("\n\n").next().unwrap(), stacks.to_string());
+        for stacks in [
+            Stacks(vec![vec![], vec!['A', 'B', 'C'], vec![], vec!['D']]),
+            Stacks(vec![vec![]]),
+        ] {
+            assert_eq!(Ok(stacks.clone()), Stacks::parse(&stacks.to_string()));
+        }
+        // With no stacks there is no footer, so the rendering has nothing to parse
+        assert_eq!("", Stacks(vec![]).to_string());
+        assert_eq!(
+            Err(MoveError::MissingDrawing),
+            Stacks::parse(&Stacks(vec![]).to_string())
+        );
+        assert_eq!(
+            "    [C]    \n    [B]    \n[D] [A]    \n 1   2   3 ",
+            Stacks(vec![vec!['D'], vec!['A', 'B', 'C'], vec![]]).to_string()
+        );
+        let mut history = History::new(EXAMPLE, CrateMover9001).unwrap();
+        while history.step().unwrap() {
+            assert_eq!(
+                Ok(history.stacks.clone()),
+                Stacks::parse(&history.stacks.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_dump() {
+        let output = dump(EXAMPLE, CrateMover9000, 3).unwrap();
+        assert_eq!(
+            "Initial:
+    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+After move 3 (line 8, move 2 from 2 to 1):
+        [Z]
+        [N]
+[M]     [D]
+[C]     [P]
+ 1   2   3 
+
+After move 4 (line 9, move 1 from 1 to 2):
+        [Z]
+        [N]
+        [D]
+[C] [M] [P]
+ 1   2   3 
+",
+            output
+        );
+        assert_eq!(
+            5,
+            dump(EXAMPLE, CrateMover9001, 1)
+                .unwrap()
+                .matches(" 1   2   3 ")
+                .count()
+        );
+        assert_eq!(
+            dump(EXAMPLE, CrateMover9001, 1),
+            dump(EXAMPLE, CrateMover9001, 0)
+        );
+    }
+
+    #[test]
+    fn test_cranes() {
+        let step = |count, start, end| Move {
+            line: 0,
+            count,
+            start,
+            end,
+        };
+        let mut stacks = Stacks(vec![vec!['A', 'B', 'C'], vec!['D']]);
+        stacks.apply(&CrateMover9000, &step(2, 1, 2)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A'], vec!['D', 'C', 'B']]), stacks);
+        stacks.apply(&CrateMover9001, &step(2, 2, 1)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A', 'C', 'B'], vec!['D']]), stacks);
+        assert_eq!("BD", stacks.tops());
+        stacks.revert(&CrateMover9001, &step(2, 2, 1)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A'], vec!['D', 'C', 'B']]), stacks);
+        stacks.revert(&CrateMover9000, &step(2, 1, 2)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A', 'B', 'C'], vec!['D']]), stacks);
+        assert_eq!(
+            Err(MoveError::NotEnoughCrates {
+                line: 0,
+                stack: 2,
+                count: 2,
+                available: 1,
+            }),
+            stacks.revert(&CrateMover9000, &step(2, 1, 2))
+        );
+        let mut stacks = Stacks(vec![vec!['A', 'B', 'C']]);
+        stacks.apply(&CrateMover9000, &step(2, 1, 1)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A', 'B', 'C']]), stacks);
+        stacks.apply(&CrateMover9001, &step(3, 1, 1)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A', 'B', 'C']]), stacks);
+        stacks.revert(&CrateMover9000, &step(2, 1, 1)).unwrap();
+        assert_eq!(Stacks(vec![vec!['A', 'B', 'C']]), stacks);
+        assert_eq!(
+            Err(MoveError::NotEnoughCrates {
+                line: 0,
+                stack: 1,
+                count: 4,
+                available: 3,
+            }),
+            stacks.apply(&CrateMover9000, &step(4, 1, 1))
+        );
+        assert_eq!(
+            Ok("C".to_string()),
+            run_crane("[C]\n[B]\n[A]\n 1\n\nmove 2 from 1 to 1", CrateMover9000)
+        );
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        assert_eq!(
+            Ok(vec![
+                Move {
+                    line: 6,
+                    count: 1,
+                    start: 2,
+                    end: 1,
+                },
+                Move {
+                    line: 7,
+                    count: 3,
+                    start: 1,
+                    end: 3,
+                },
+                Move {
+                    line: 8,
+                    count: 2,
+                    start: 2,
+                    end: 1,
+                },
+                Move {
+                    line: 9,
+                    count: 1,
+                    start: 1,
+                    end: 2,
+                },
+            ]),
+            parse_moves(EXAMPLE)
+        );
+        assert_eq!(
+            "move 3 from 1 to 3",
+            parse_moves(EXAMPLE).unwrap()[1].to_string()
+        );
+        assert_eq!(
+            Err(MoveError::InvalidMove {
+                line: 4,
+                content: "move 1 from 2".to_string(),
+            }),
+            parse_moves(" 1   2\n\nmove 1 from 1 to 2\nmove 1 from 2")
+        );
+    }
+
+    #[test]
+    fn test_move_parse() {
+        assert_eq!(
+            Ok(Move {
+                line: 3,
+                count: 12,
+                start: 1,
+                end: 9,
+            }),
+            Move::parse(3, " move 12 from 1 to 9 ")
+        );
+        assert_eq!(
+            Err(MoveError::InvalidMove {
+                line: 5,
+                content: "move 99999999999999999999 from 1 to 2".to_string(),
+            }),
+            Move::parse(5, "move 99999999999999999999 from 1 to 2")
+        );
+    }
+
+    #[test]
+    fn test_move_errors() {
+        let drawing = "[A]\n 1   2\n\n";
+        assert_eq!(
+            Err(MoveError::UnknownStack { line: 4, stack: 3 }),
+            run_crane(&format!("{}move 1 from 1 to 3", drawing), CrateMover9000)
+        );
         assert_eq!(
-            "CMZ".to_string(),
-            determine_final_stack_tops(
-                "    [D]
-                [N] [C]
-                [Z] [M] [P]
-                 1   2   3
+            Err(MoveError::UnknownStack { line: 4, stack: 0 }),
+            run_crane(&format!("{}move 1 from 0 to 1", drawing), CrateMover9000)
+        );
+        let error = run_crane(
+            &format!("{}move 1 from 1 to 2\nmove 2 from 2 to 1", drawing),
+            CrateMover9001,
+        )
+        .unwrap_err();
+        assert_eq!(
+            MoveError::NotEnoughCrates {
+                line: 5,
+                stack: 2,
+                count: 2,
+                available: 1,
+            },
+            error
+        );
+        assert_eq!(
+            "Line 5: cannot move 2 crates from stack 2, which holds 1",
+            error.to_string()
+        );
+        assert_eq!(
+            Ok(" A".to_string()),
+            run_crane(&format!("{}move 1 from 1 to 2", drawing), CrateMover9000)
+        );
+    }
+
+    #[test]
+    fn test_reverse() {
+        let moves = EXAMPLE.split("\n\n").nth(1).unwrap();
+        let initial = Stacks::parse(EXAMPLE).unwrap();
+        let mut history = History::new(EXAMPLE, CrateMover9000).unwrap();
+        history.run().unwrap();
+        assert_eq!(
+            Ok(initial.clone()),
+            reverse(&format!("{}\n\n{}", history.stacks, moves), CrateMover9000)
+        );
+        let mut history = History::new(EXAMPLE, CrateMover9001).unwrap();
+        history.run().unwrap();
+        assert_eq!(
+            Ok(initial),
+            reverse(&format!("{}\n\n{}", history.stacks, moves), CrateMover9001)
+        );
+        assert_eq!(
+            Err(MoveError::NotEnoughCrates {
+                line: 4,
+                stack: 2,
+                count: 1,
+                available: 0,
+            }),
+            reverse("[A]\n 1   2\n\nmove 1 from 1 to 2", CrateMover9000)
+        );
+    }
+
+    // Small xorshift generator so the property test needs no extra dependencies
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
 
-                move 1 from 2 to 1
-                move 3 from 1 to 3
-                move 2 from 2 to 1
-                move 1 from 1 to 2
-                ",
-                stacks
-            )
+    fn round_trip(crane: impl Crane + Clone, seed: u64) {
+        let mut state = seed;
+        let count = 1 + next_random(&mut state) as usize % 9;
+        let mut stacks = Stacks(
+            (0..count)
+                .map(|_| {
+                    (0..next_random(&mut state) % 8)
+                        .map(|_| (b'A' + (next_random(&mut state) % 26) as u8) as char)
+                        .collect()
+                })
+                .collect(),
         );
+        let initial = stacks.clone();
+        let mut moves = Vec::new();
+        for _ in 0..next_random(&mut state) % 30 {
+            let start = 1 + next_random(&mut state) as usize % count;
+            let end = 1 + next_random(&mut state) as usize % count;
+            let available = stacks.0[start - 1].len();
+            let step = Move {
+                line: 0,
+                count: next_random(&mut state) as usize % (available + 1),
+                start,
+                end,
+            };
+            stacks.apply(&crane, &step).unwrap();
+            moves.push(step.to_string());
+        }
+        let input = format!("{}\n\n{}", stacks, moves.join("\n"));
+        assert_eq!(Ok(initial.clone()), reverse(&input, crane.clone()));
+        let input = format!("{}\n\n{}", initial, moves.join("\n"));
+        assert_eq!(Ok(stacks.tops()), run_crane(&input, crane));
+    }
+
+    #[test]
+    fn test_reverse_round_trip() {
+        for seed in 1..500 {
+            round_trip(CrateMover9000, seed * 7919);
+            round_trip(CrateMover9001, seed * 7919);
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = History::new(EXAMPLE, CrateMover9000).unwrap();
+        let initial = history.stacks.clone();
+        assert!(!history.undo());
+        history.replay_to(2).unwrap();
+        assert_eq!(2, history.applied);
+        assert_eq!(" CZ", history.stacks.tops());
+        history.run().unwrap();
+        assert_eq!("CMZ", history.stacks.tops());
+        assert!(!history.step().unwrap());
+        history.replay_to(1).unwrap();
+        assert_eq!("DCP", history.stacks.tops());
+        assert!(history.undo());
+        assert_eq!(initial, history.stacks);
+        history.replay_to(100).unwrap();
+        assert_eq!(4, history.applied);
+        let mut history = History::new(EXAMPLE, CrateMover9001).unwrap();
+        history.run().unwrap();
+        assert_eq!("MCD", history.stacks.tops());
+        history.replay_to(0).unwrap();
+        assert_eq!(initial, history.stacks);
+    }
+
+    #[test]
+    fn test_determine_final_stack_top() {
+        assert_eq!(Ok("CMZ".to_string()), determine_final_stack_tops(EXAMPLE));
     }
 
     #[test]
     fn test_determine_final_stack_tops_9001() {
-        let stacks = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
-        assert_eq!(
-            "MCD".to_string(),
-            determine_final_stack_tops_9001(
-                "    [D]
-                [N] [C]
-                [Z] [M] [P]
-                 1   2   3
-
-                move 1 from 2 to 1
-                move 3 from 1 to 3
-                move 2 from 2 to 1
-                move 1 from 1 to 2
-                ",
-                stacks
-            )
+        assert_eq!(
+            Ok("MCD".to_string()),
+            determine_final_stack_tops_9001(EXAMPLE)
+        );
+        assert_eq!(
+            Ok("DCP".to_string()),
+            tops_after(EXAMPLE, CrateMover9000, 1)
+        );
+        assert_eq!(
+            Err(MoveError::MissingDrawing),
+            determine_final_stack_tops_9001("")
+        );
+    }
+
+    #[test]
+    fn test_crate_simulation() {
+        let mut simulation = CrateSimulation::new(EXAMPLE, CrateMover9000).unwrap();
+        assert_eq!(vec!["move 1 from 2 to 1"], simulation.panes()[1].1);
+        assert!(simulation.step());
+        assert!(simulation.step());
+        assert_eq!(vec!["1: ", "2: M C", "3: P D N Z"], simulation.panes()[0].1);
+        assert_eq!(
+            vec![
+                ("move".to_string(), 2),
+                ("remaining".to_string(), 2),
+                ("stack1.height".to_string(), 0),
+                ("stack2.height".to_string(), 2),
+                ("stack3.height".to_string(), 4),
+            ],
+            simulation.fields()
+        );
+        let mut simulation = CrateSimulation::new(EXAMPLE, CrateMover9001).unwrap();
+        while simulation.step() {}
+        assert_eq!(vec!["1: M", "2: C", "3: P Z N D"], simulation.panes()[0].1);
+        assert_eq!(vec!["done"], simulation.panes()[1].1);
+        let mut simulation =
+            CrateSimulation::new("[A]\n 1   2\n\nmove 2 from 1 to 2", CrateMover9000).unwrap();
+        assert!(!simulation.step());
+        assert_eq!(
+            vec!["Line 4: cannot move 2 crates from stack 1, which holds 1"],
+            simulation.panes()[1].1
         );
     }
 }
@@ -12,33 +12,115 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use aoc::variants::Variants;
 use std::collections::HashSet;
+use std::env::args;
 use std::fs::read_to_string;
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
-    let input = read_to_string("input.txt").expect("Unable to read input file");
-    println!("Part 1: {}", find_marker(&input, 4));
-    println!("Part 2: {}", find_marker(&input, 14));
+    let args = args().collect::<Vec<String>>();
+    let input = match args.iter().position(|arg| "--benchmark" == arg) {
+        Some(index) => synthetic_stream(
+            args.get(index + 1)
+                .and_then(|size| size.parse::<usize>().ok())
+                .unwrap_or(1)
+                * 1024
+                * 1024,
+        ),
+        None => read_to_string("input.txt").expect("Unable to read input file"),
+    };
+    Variants::new()
+        .register(1, "counting", |input| find_marker(input, 4).to_string())
+        .register(1, "last-seen", |input| {
+            find_marker_last_seen(input, 4).to_string()
+        })
+        .register(1, "hashset", |input| {
+            find_marker_hashset(input, 4).to_string()
+        })
+        .register(2, "counting", |input| find_marker(input, 14).to_string())
+        .register(2, "last-seen", |input| {
+            find_marker_last_seen(input, 14).to_string()
+        })
+        .register(2, "hashset", |input| {
+            find_marker_hashset(input, 14).to_string()
+        })
+        .run_from_args(&input);
 }
 
+// Every window of four repeats a symbol, so neither marker appears until the
+// fourteen distinct letters at the very end
+fn synthetic_stream(length: usize) -> String {
+    let mut stream = "abca".repeat(length.saturating_sub(14) / 4);
+    stream.push_str("abcdefghijklmn");
+    stream
+}
+
+// The datastream is treated as raw bytes: every byte is one symbol and positions
+// are byte offsets, so a non-ASCII character counts as each of its UTF-8 bytes.
+// Returns 0 when no marker is found.
 fn find_marker(input: &str, marker_length: usize) -> u32 {
-    if marker_length > input.len() {
+    let bytes = input.as_bytes();
+    if 0 == marker_length || marker_length > bytes.len() {
         return 0;
     }
-    for index in (marker_length - 1)..input.len() {
-        let set = input
-            .chars()
-            .skip((index + 1) - marker_length)
-            .take(marker_length)
-            .collect::<HashSet<char>>();
-        if marker_length == set.len() {
+    let mut counts = [0usize; 256];
+    let mut duplicates = 0;
+    for (index, &byte) in bytes.iter().enumerate() {
+        counts[byte as usize] += 1;
+        if 2 == counts[byte as usize] {
+            duplicates += 1;
+        }
+        if index >= marker_length {
+            let expired = bytes[index - marker_length] as usize;
+            counts[expired] -= 1;
+            if 1 == counts[expired] {
+                duplicates -= 1;
+            }
+        }
+        if index + 1 >= marker_length && 0 == duplicates {
             return index as u32 + 1;
         }
     }
     0
 }
 
+// Same contract as find_marker, but jumps the window start past the previous
+// copy of each byte instead of counting
+fn find_marker_last_seen(input: &str, marker_length: usize) -> u32 {
+    let bytes = input.as_bytes();
+    if 0 == marker_length || marker_length > bytes.len() {
+        return 0;
+    }
+    // One past the last index each byte was seen at, so 0 means never
+    let mut last_seen = [0usize; 256];
+    let mut window_start = 0;
+    for (index, &byte) in bytes.iter().enumerate() {
+        window_start = window_start.max(last_seen[byte as usize]);
+        last_seen[byte as usize] = index + 1;
+        if index + 1 - window_start >= marker_length {
+            return index as u32 + 1;
+        }
+    }
+    0
+}
+
+// Same contract as find_marker, but builds a fresh set for every window. Kept as
+// the baseline for benchmarks, so it costs the marker length per byte rather than
+// rescanning the stream.
+fn find_marker_hashset(input: &str, marker_length: usize) -> u32 {
+    let bytes = input.as_bytes();
+    if 0 == marker_length || marker_length > bytes.len() {
+        return 0;
+    }
+    for (index, window) in bytes.windows(marker_length).enumerate() {
+        if marker_length == window.iter().collect::<HashSet<&u8>>().len() {
+            return (index + marker_length) as u32;
+        }
+    }
+    0
+}
+
 #[cfg(not(tarpaulin_include))]
 #[cfg(test)]
 mod tests {
@@ -65,4 +147,50 @@ mod tests {
         assert_eq!(29, find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14));
         assert_eq!(26, find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14));
     }
+
+    #[test]
+    fn test_variants_agree() {
+        for input in [
+            "abc",
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabcdefghijklmnopqrstuvwxyz",
+            "ééab",
+        ] {
+            for marker_length in [0, 1, 4, 14] {
+                let expected = find_marker(input, marker_length);
+                assert_eq!(expected, find_marker_last_seen(input, marker_length));
+                assert_eq!(expected, find_marker_hashset(input, marker_length));
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_marker_non_ascii() {
+        // é is the two bytes C3 A9, so "éa" is already three distinct symbols
+        assert_eq!(3, find_marker("éab", 3));
+        assert_eq!(4, find_marker("éab", 4));
+        // Both copies of é share their bytes, so the marker straddles the second one
+        assert_eq!(0, find_marker("éé", 3));
+        assert_eq!(5, find_marker("ééab", 3));
+        assert_eq!(1, find_marker("é", 1));
+        assert_eq!(0, find_marker("abc", 0));
+    }
+
+    #[test]
+    fn test_synthetic_stream() {
+        let stream = synthetic_stream(1024 * 1024);
+        assert!(stream.len() <= 1024 * 1024);
+        assert!(stream.len() > 1024 * 1024 - 4);
+        assert_eq!(stream.len() as u32, find_marker(&stream, 14));
+        assert_eq!(stream.len() as u32 - 10, find_marker(&stream, 4));
+        assert_eq!(find_marker(&stream, 4), find_marker_last_seen(&stream, 4));
+        assert_eq!(find_marker(&stream, 14), find_marker_last_seen(&stream, 14));
+        let stream = synthetic_stream(64 * 1024);
+        assert_eq!(find_marker(&stream, 14), find_marker_hashset(&stream, 14));
+        assert_eq!("abcdefghijklmn", synthetic_stream(0));
+    }
 }
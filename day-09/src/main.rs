@@ -16,25 +16,19 @@ use std::collections::BTreeSet;
 use std::fs::read_to_string;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 struct Point {
     x: i32,
     y: i32,
 }
 
-impl Default for Point {
-    fn default() -> Self {
-        Point { x: 0, y: 0 }
-    }
-}
-
 impl Point {
     fn new(x: i32, y: i32) -> Point {
         Point { x, y }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Movement {
     x: i32,
     y: i32,
@@ -43,7 +37,7 @@ struct Movement {
 impl FromStr for Movement {
     type Err = ();
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let items = input.trim().split_whitespace().collect::<Vec<&str>>();
+        let items = input.split_whitespace().collect::<Vec<&str>>();
         let direction = items[0].chars().next().unwrap();
         let magnitude = items[1].parse::<i32>().unwrap();
         let (x, y) = match direction {
@@ -57,7 +51,7 @@ impl FromStr for Movement {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 struct Rope {
     head: Point,
     tail: Point,
@@ -85,8 +79,8 @@ impl Rope {
                 self.head.y + movement.y.signum(),
             );
             let mut head_in_range = false;
-            for y in vec![-1, 0, 1] {
-                for x in vec![-1, 0, 1] {
+            for y in [-1, 0, 1] {
+                for x in [-1, 0, 1] {
                     let point = Point::new(self.tail.x + x, self.tail.y + y);
                     if point == self.head {
                         head_in_range = true;
@@ -135,7 +129,7 @@ impl Rope {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 struct KnottedRope {
     knots: Vec<Point>,
     tail_visited: BTreeSet<Point>,
@@ -166,8 +160,8 @@ impl KnottedRope {
             );
             for index in 1..self.knots.len() {
                 let mut in_range = false;
-                for y in vec![-1, 0, 1] {
-                    for x in vec![-1, 0, 1] {
+                for y in [-1, 0, 1] {
+                    for x in [-1, 0, 1] {
                         let point = Point::new(self.knots[index].x + x, self.knots[index].y + y);
                         if point == self.knots[index - 1] {
                             in_range = true;
@@ -218,9 +212,157 @@ impl KnottedRope {
     }
 }
 
+#[cfg(any(feature = "explorer", test))]
+mod simulation {
+    use super::*;
+    use aoc::explorer::Simulation;
+
+    pub trait Knots {
+        fn apply(&mut self, movement: Movement);
+        fn knot_positions(&self) -> Vec<Point>;
+        fn visited(&self) -> &BTreeSet<Point>;
+    }
+
+    impl Knots for Rope {
+        fn apply(&mut self, movement: Movement) {
+            self.move_rope(movement);
+        }
+
+        fn knot_positions(&self) -> Vec<Point> {
+            vec![self.head, self.tail]
+        }
+
+        fn visited(&self) -> &BTreeSet<Point> {
+            &self.tail_visited
+        }
+    }
+
+    impl Knots for KnottedRope {
+        fn apply(&mut self, movement: Movement) {
+            self.move_rope(movement);
+        }
+
+        fn knot_positions(&self) -> Vec<Point> {
+            self.knots.clone()
+        }
+
+        fn visited(&self) -> &BTreeSet<Point> {
+            &self.tail_visited
+        }
+    }
+
+    pub const GRID_WIDTH: i32 = 21;
+    pub const GRID_HEIGHT: i32 = 11;
+
+    #[derive(Debug, Clone)]
+    pub struct RopeSimulation<R: Knots> {
+        rope: R,
+        pending: Vec<Movement>,
+        steps: usize,
+    }
+
+    impl<R: Knots> RopeSimulation<R> {
+        pub fn new(rope: R, input: &str) -> RopeSimulation<R> {
+            let mut pending = Vec::new();
+            for line in input.trim().lines() {
+                let movement = line.parse::<Movement>().unwrap();
+                for _ in 0..movement.x.abs().max(movement.y.abs()) {
+                    pending.push(Movement {
+                        x: movement.x.signum(),
+                        y: movement.y.signum(),
+                    });
+                }
+            }
+            pending.reverse();
+            RopeSimulation {
+                rope,
+                pending,
+                steps: 0,
+            }
+        }
+
+        fn grid(&self) -> Vec<String> {
+            let knots = self.rope.knot_positions();
+            let head = knots[0];
+            let mut lines = Vec::new();
+            for y in (head.y - GRID_HEIGHT / 2..=head.y + GRID_HEIGHT / 2).rev() {
+                let mut line = String::new();
+                for x in head.x - GRID_WIDTH / 2..=head.x + GRID_WIDTH / 2 {
+                    let point = Point::new(x, y);
+                    let symbol = match knots.iter().position(|knot| *knot == point) {
+                        Some(0) => 'H',
+                        Some(index) if index == knots.len() - 1 => 'T',
+                        Some(index) => char::from_digit(index as u32, 10).unwrap_or('K'),
+                        None if Point::default() == point => 's',
+                        None if self.rope.visited().contains(&point) => '#',
+                        None => '.',
+                    };
+                    line.push(symbol);
+                }
+                lines.push(line);
+            }
+            lines
+        }
+    }
+
+    impl<R: Knots + Clone> Simulation for RopeSimulation<R> {
+        fn step(&mut self) -> bool {
+            match self.pending.pop() {
+                Some(movement) => {
+                    self.rope.apply(movement);
+                    self.steps += 1;
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn fields(&self) -> Vec<(String, i64)> {
+            let knots = self.rope.knot_positions();
+            let tail = knots[knots.len() - 1];
+            vec![
+                ("step".to_string(), self.steps as i64),
+                ("head.x".to_string(), knots[0].x as i64),
+                ("head.y".to_string(), knots[0].y as i64),
+                ("tail.x".to_string(), tail.x as i64),
+                ("tail.y".to_string(), tail.y as i64),
+                ("visited".to_string(), self.rope.visited().len() as i64),
+                ("remaining".to_string(), self.pending.len() as i64),
+            ]
+        }
+
+        fn panes(&self) -> Vec<(String, Vec<String>)> {
+            let knots = self
+                .rope
+                .knot_positions()
+                .iter()
+                .enumerate()
+                .map(|(index, knot)| format!("{}: ({}, {})", index, knot.x, knot.y))
+                .collect();
+            vec![
+                ("Knots".to_string(), knots),
+                ("Grid".to_string(), self.grid()),
+            ]
+        }
+    }
+}
+
 #[cfg(not(tarpaulin_include))]
 fn main() {
     let input = read_to_string("input.txt").expect("Unable to read input file");
+    #[cfg(feature = "explorer")]
+    if let Some(part) = aoc::explorer::requested_part(std::env::args()) {
+        if 1 == part {
+            aoc::explorer::explore(simulation::RopeSimulation::new(Rope::default(), &input))
+        } else {
+            aoc::explorer::explore(simulation::RopeSimulation::new(
+                KnottedRope::default(),
+                &input,
+            ))
+        }
+        .expect("Unable to run explorer");
+        return;
+    }
     let mut rope = Rope::default();
     rope.parse_movements(&input);
     println!("Part 1: {}", rope.tail_visited.len());
@@ -232,7 +374,9 @@ fn main() {
 #[cfg(not(tarpaulin_include))]
 #[cfg(test)]
 mod tests {
+    use super::simulation::*;
     use super::*;
+    use aoc::explorer::Simulation;
 
     #[test]
     fn test_default_point() {
@@ -365,4 +509,47 @@ mod tests {
         );
         assert_eq!(36, knotted_rope.tail_visited.len());
     }
+
+    #[test]
+    fn test_rope_simulation() {
+        let mut simulation = RopeSimulation::new(Rope::default(), "R 4\nU 4");
+        assert_eq!(("remaining".to_string(), 8), simulation.fields()[6]);
+        let mut steps = 0;
+        while simulation.step() {
+            steps += 1;
+        }
+        assert_eq!(8, steps);
+        assert_eq!(
+            vec![
+                ("step".to_string(), 8),
+                ("head.x".to_string(), 4),
+                ("head.y".to_string(), 4),
+                ("tail.x".to_string(), 4),
+                ("tail.y".to_string(), 3),
+                ("visited".to_string(), 7),
+                ("remaining".to_string(), 0),
+            ],
+            simulation.fields()
+        );
+        let panes = simulation.panes();
+        assert_eq!(vec!["0: (4, 4)", "1: (4, 3)"], panes[0].1);
+        let grid = &panes[1].1;
+        assert_eq!(GRID_HEIGHT as usize, grid.len());
+        assert_eq!("..........H..........", grid[5]);
+        assert_eq!("..........T..........", grid[6]);
+        assert_eq!("......s###...........", grid[9]);
+    }
+
+    #[test]
+    fn test_knotted_rope_simulation() {
+        let mut simulation = RopeSimulation::new(KnottedRope::default(), "R 5\nU 8");
+        for _ in 0..13 {
+            assert!(simulation.step());
+        }
+        assert!(!simulation.step());
+        let panes = simulation.panes();
+        assert_eq!(10, panes[0].1.len());
+        assert_eq!("0: (5, 8)", panes[0].1[0]);
+        assert_eq!("9: (0, 0)", panes[0].1[9]);
+    }
 }
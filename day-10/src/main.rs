@@ -21,7 +21,7 @@ enum CycleTime {
     Addx = 2,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 enum Action {
     Noop(i32),
     Addx(i32),
@@ -30,7 +30,7 @@ enum Action {
 impl FromStr for Action {
     type Err = ();
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut split_input = input.trim().split_whitespace();
+        let mut split_input = input.split_whitespace();
         let action = split_input.next().unwrap();
         match action {
             "noop" => Ok(Action::Noop(0)),
@@ -43,7 +43,7 @@ impl FromStr for Action {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct Program {
     actions: Vec<Action>,
     cycles: u32,
@@ -123,9 +123,60 @@ impl Program {
     }
 }
 
+#[cfg(any(feature = "explorer", test))]
+mod simulation {
+    use super::*;
+    use aoc::explorer::Simulation;
+
+    impl Simulation for Program {
+        fn step(&mut self) -> bool {
+            if self.actions.is_empty() {
+                return false;
+            }
+            self.run_cycle();
+            true
+        }
+
+        fn fields(&self) -> Vec<(String, i64)> {
+            vec![
+                ("cycle".to_string(), self.cycles as i64),
+                ("x".to_string(), self.x as i64),
+                ("signal".to_string(), self.signal_strength as i64),
+                ("crt_row".to_string(), self.crt_index as i64),
+                ("pending".to_string(), self.actions.len() as i64),
+            ]
+        }
+
+        fn panes(&self) -> Vec<(String, Vec<String>)> {
+            let pending = match self.actions.last() {
+                Some(Action::Noop(_)) => "noop".to_string(),
+                Some(Action::Addx(value)) => format!("addx {}", value),
+                None => "none".to_string(),
+            };
+            vec![
+                (
+                    "Pending action".to_string(),
+                    vec![
+                        pending,
+                        format!("started at cycle {}", self.previous_action_cycle),
+                    ],
+                ),
+                ("CRT".to_string(), self.crt.clone()),
+            ]
+        }
+    }
+}
+
 #[cfg(not(tarpaulin_include))]
 fn main() {
     let input = read_to_string("input.txt").expect("Unable to read input file");
+    #[cfg(feature = "explorer")]
+    if aoc::explorer::requested_part(std::env::args()).is_some() {
+        let mut program = Program::default();
+        program.parse_actions(&input);
+        aoc::explorer::explore(program).expect("Unable to run explorer");
+        return;
+    }
     let mut part1 = Program::default();
     part1.parse_actions(&input);
     part1.run();
@@ -140,6 +191,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc::explorer::Simulation;
 
     #[test]
     fn test_noop_action_from_str() {
@@ -400,4 +452,36 @@ mod tests {
             .to_string();
         assert_eq!(expected_crt, program.crt.join("\n"));
     }
+
+    #[test]
+    fn test_program_simulation() {
+        let mut program = Program::default();
+        program.parse_actions(
+            "noop
+            addx 3
+            addx -5
+            ",
+        );
+        assert_eq!(
+            vec!["noop".to_string(), "started at cycle 0".to_string()],
+            program.panes()[0].1
+        );
+        let mut steps = 0;
+        while program.step() {
+            steps += 1;
+        }
+        assert_eq!(5, steps);
+        assert!(!program.step());
+        assert_eq!(
+            vec![
+                ("cycle".to_string(), 5),
+                ("x".to_string(), -1),
+                ("signal".to_string(), 0),
+                ("crt_row".to_string(), 0),
+                ("pending".to_string(), 0),
+            ],
+            program.fields()
+        );
+        assert_eq!("#####", program.panes()[1].1[0]);
+    }
 }
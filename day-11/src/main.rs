@@ -13,10 +13,11 @@
 // limitations under the License.
 
 #![allow(unused_imports)]
+use aoc::variants::Variants;
 use num_bigint::BigUint;
 use std::ops::{Add, Div, Mul, Rem};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Monkey {
     starting_items: Vec<BigUint>,
     operation: fn(BigUint) -> BigUint,
@@ -61,6 +62,7 @@ impl Monkey {
     }
 }
 
+#[derive(Debug, Clone)]
 struct Monkeys(Vec<Monkey>);
 
 impl Monkeys {
@@ -101,9 +103,91 @@ impl Monkeys {
     }
 }
 
+#[cfg(any(feature = "explorer", test))]
+mod simulation {
+    use super::*;
+    use aoc::explorer::Simulation;
+
+    #[derive(Debug, Clone)]
+    pub struct MonkeySimulation {
+        monkeys: Monkeys,
+        rounds: u32,
+        round: u32,
+        factor: BigUint,
+        reduction_factor: BigUint,
+    }
+
+    impl MonkeySimulation {
+        pub fn new(
+            monkeys: Monkeys,
+            rounds: u32,
+            factor: BigUint,
+            reduction_factor: BigUint,
+        ) -> MonkeySimulation {
+            MonkeySimulation {
+                monkeys,
+                rounds,
+                round: 0,
+                factor,
+                reduction_factor,
+            }
+        }
+    }
+
+    impl Simulation for MonkeySimulation {
+        fn step(&mut self) -> bool {
+            if self.round == self.rounds {
+                return false;
+            }
+            self.monkeys
+                .round(self.factor.clone(), self.reduction_factor.clone());
+            self.round += 1;
+            true
+        }
+
+        fn fields(&self) -> Vec<(String, i64)> {
+            let mut fields = vec![("round".to_string(), self.round as i64)];
+            for (index, monkey) in self.monkeys.0.iter().enumerate() {
+                fields.push((
+                    format!("monkey{}.items", index),
+                    monkey.starting_items.len() as i64,
+                ));
+                fields.push((
+                    format!("monkey{}.inspections", index),
+                    monkey.inspection_count as i64,
+                ));
+            }
+            fields
+        }
+
+        fn panes(&self) -> Vec<(String, Vec<String>)> {
+            let monkeys = self
+                .monkeys
+                .0
+                .iter()
+                .enumerate()
+                .map(|(index, monkey)| {
+                    format!(
+                        "Monkey {} ({} inspections): {}",
+                        index,
+                        monkey.inspection_count,
+                        monkey
+                            .starting_items
+                            .iter()
+                            .map(|item| item.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                })
+                .collect();
+            vec![("Monkeys".to_string(), monkeys)]
+        }
+    }
+}
+
 #[cfg(not(tarpaulin_include))]
-fn main() {
-    let mut monkeys1: Monkeys = Monkeys(vec![
+fn build_monkeys() -> Monkeys {
+    Monkeys(vec![
         //         Monkey 0:
         //   Starting items: 66, 71, 94
         //   Operation: new = old * 5
@@ -298,226 +382,63 @@ fn main() {
             4,
             6,
         ),
-    ]);
-    println!(
-        "Part 1: {}",
-        monkeys1.monkey_business(20, BigUint::from(3u32), BigUint::from(1u32))
-    );
-    let mut monkeys2: Monkeys = Monkeys(vec![
-        //         Monkey 0:
-        //   Starting items: 66, 71, 94
-        //   Operation: new = old * 5
-        //   Test: divisible by 3
-        //     If true: throw to monkey 7
-        //     If false: throw to monkey 4
-        Monkey::new(
-            vec![
-                BigUint::from(66u32),
-                BigUint::from(71u32),
-                BigUint::from(94u32),
-            ],
-            |old| old * BigUint::from(5u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(3u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            7,
-            4,
-        ),
-        //         Monkey 1:
-        //   Starting items: 70
-        //   Operation: new = old + 6
-        //   Test: divisible by 17
-        //     If true: throw to monkey 3
-        //     If false: throw to monkey 0
-        Monkey::new(
-            vec![BigUint::from(70u32)],
-            |old| old + BigUint::from(6u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(17u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            3,
-            0,
-        ),
-        //         Monkey 2:
-        //   Starting items: 62, 68, 56, 65, 94, 78
-        //   Operation: new = old + 5
-        //   Test: divisible by 2
-        //     If true: throw to monkey 3
-        //     If false: throw to monkey 1
-        Monkey::new(
-            vec![
-                BigUint::from(62u32),
-                BigUint::from(68u32),
-                BigUint::from(56u32),
-                BigUint::from(65u32),
-                BigUint::from(94u32),
-                BigUint::from(78u32),
-            ],
-            |old| old + BigUint::from(5u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(2u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            3,
-            1,
-        ),
-        //         Monkey 3:
-        //   Starting items: 89, 94, 94, 67
-        //   Operation: new = old + 2
-        //   Test: divisible by 19
-        //     If true: throw to monkey 7
-        //     If false: throw to monkey 0
-        Monkey::new(
-            vec![
-                BigUint::from(89u32),
-                BigUint::from(94u32),
-                BigUint::from(94u32),
-                BigUint::from(67u32),
-            ],
-            |old| old + BigUint::from(2u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(19u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            7,
-            0,
-        ),
-        //         Monkey 4:
-        //   Starting items: 71, 61, 73, 65, 98, 98, 63
-        //   Operation: new = old * 7
-        //   Test: divisible by 11
-        //     If true: throw to monkey 5
-        //     If false: throw to monkey 6
-        Monkey::new(
-            vec![
-                BigUint::from(71u32),
-                BigUint::from(61u32),
-                BigUint::from(73u32),
-                BigUint::from(65u32),
-                BigUint::from(98u32),
-                BigUint::from(98u32),
-                BigUint::from(63u32),
-            ],
-            |old| old * BigUint::from(7u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(11u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            5,
-            6,
-        ),
-        //         Monkey 5:
-        //   Starting items: 55, 62, 68, 61, 60
-        //   Operation: new = old + 7
-        //   Test: divisible by 5
-        //     If true: throw to monkey 2
-        //     If false: throw to monkey 1
-        Monkey::new(
-            vec![
-                BigUint::from(55u32),
-                BigUint::from(62u32),
-                BigUint::from(68u32),
-                BigUint::from(61u32),
-                BigUint::from(60u32),
-            ],
-            |old| old + BigUint::from(7u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(5u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            2,
-            1,
-        ),
-        //         Monkey 6:
-        //   Starting items: 93, 91, 69, 64, 72, 89, 50, 71
-        //   Operation: new = old + 1
-        //   Test: divisible by 13
-        //     If true: throw to monkey 5
-        //     If false: throw to monkey 2
-        Monkey::new(
-            vec![
-                BigUint::from(93u32),
-                BigUint::from(91u32),
-                BigUint::from(69u32),
-                BigUint::from(64u32),
-                BigUint::from(72u32),
-                BigUint::from(89u32),
-                BigUint::from(50u32),
-                BigUint::from(71u32),
-            ],
-            |old| old + BigUint::from(1u32),
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(13u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            5,
-            2,
-        ),
-        //         Monkey 7:
-        //   Starting items: 76, 50
-        //   Operation: new = old * old
-        //   Test: divisible by 7
-        //     If true: throw to monkey 4
-        //     If false: throw to monkey 6
-        Monkey::new(
-            vec![BigUint::from(76u32), BigUint::from(50u32)],
-            |old| {
-                let factor = old.clone();
-                old * factor
-            },
-            |item, true_index, false_index| {
-                if BigUint::from(0u32) == item.clone().rem(BigUint::from(7u32)) {
-                    (true_index, item)
-                } else {
-                    (false_index, item)
-                }
-            },
-            4,
-            6,
-        ),
-    ]);
-    let factor = BigUint::from(3u32)
+    ])
+}
+
+#[cfg(not(tarpaulin_include))]
+fn reduction_factor() -> BigUint {
+    BigUint::from(3u32)
         * BigUint::from(17u32)
         * BigUint::from(2u32)
         * BigUint::from(19u32)
         * BigUint::from(11u32)
         * BigUint::from(5u32)
         * BigUint::from(13u32)
-        * BigUint::from(7u32);
+        * BigUint::from(7u32)
+}
 
-    println!(
-        "Part 2: {}",
-        monkeys2.monkey_business(10000, BigUint::from(1u32), factor)
-    );
+#[cfg(not(tarpaulin_include))]
+fn main() {
+    #[cfg(feature = "explorer")]
+    if let Some(part) = aoc::explorer::requested_part(std::env::args()) {
+        let simulation = if 1 == part {
+            simulation::MonkeySimulation::new(
+                build_monkeys(),
+                20,
+                BigUint::from(3u32),
+                BigUint::from(1u32),
+            )
+        } else {
+            simulation::MonkeySimulation::new(
+                build_monkeys(),
+                10000,
+                BigUint::from(1u32),
+                reduction_factor(),
+            )
+        };
+        aoc::explorer::explore(simulation).expect("Unable to run explorer");
+        return;
+    }
+    Variants::new()
+        .register(1, "biguint", |_| {
+            build_monkeys()
+                .monkey_business(20, BigUint::from(3u32), BigUint::from(1u32))
+                .to_string()
+        })
+        .register(2, "biguint", |_| {
+            build_monkeys()
+                .monkey_business(10000, BigUint::from(1u32), reduction_factor())
+                .to_string()
+        })
+        .run_from_args("");
 }
 
 #[cfg(not(tarpaulin_include))]
 #[cfg(test)]
 mod tests {
+    use super::simulation::*;
     use super::*;
+    use aoc::explorer::Simulation;
 
     #[test]
     fn test_monkey_new() {
@@ -739,4 +660,46 @@ mod tests {
             monkeys.monkey_business(20, BigUint::new(vec![3]), reduction_factor.clone())
         );
     }
+
+    #[test]
+    fn test_monkey_simulation() {
+        let monkeys = Monkeys(vec![
+            Monkey::new(
+                vec![BigUint::from(2u32), BigUint::from(3u32)],
+                |old| old,
+                |item, true_index, _| (true_index, item),
+                1,
+                1,
+            ),
+            Monkey::new(
+                vec![],
+                |old| old,
+                |item, true_index, _| (true_index, item),
+                0,
+                0,
+            ),
+        ]);
+        let mut simulation =
+            MonkeySimulation::new(monkeys, 2, BigUint::from(1u32), BigUint::from(10u32));
+        assert!(simulation.step());
+        assert_eq!(
+            vec![
+                ("round".to_string(), 1),
+                ("monkey0.items".to_string(), 2),
+                ("monkey0.inspections".to_string(), 2),
+                ("monkey1.items".to_string(), 0),
+                ("monkey1.inspections".to_string(), 2),
+            ],
+            simulation.fields()
+        );
+        assert_eq!(
+            vec![
+                "Monkey 0 (2 inspections): 2, 3".to_string(),
+                "Monkey 1 (2 inspections): ".to_string(),
+            ],
+            simulation.panes()[0].1
+        );
+        assert!(simulation.step());
+        assert!(!simulation.step());
+    }
 }